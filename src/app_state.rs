@@ -1,41 +1,167 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, PoisonError, RwLock, RwLockReadGuard,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use anyhow::{Context as _, Result};
 use dashmap::DashMap;
 use serenity::{
     client::{Client, Context},
-    model::{
-        channel::Message,
-        id::{ChannelId, GuildId},
-    },
+    model::id::{ChannelId, GuildId},
     prelude::TypeMapKey,
 };
+use tokio::sync::{mpsc, watch};
+use tracing::info;
 
-use crate::tts::voicevox::VoicevoxClient;
+use crate::{
+    config::{CatchupConfig, InFlightPolicy},
+    db::Storage,
+    message::QueuedMessage,
+    tts::voicevox::VoicevoxClient,
+};
+
+/// The longest time to hold messages until the speakers are initialized
+///
+/// Messages keep arriving while the reader waits, so the wait is bounded to avoid letting the
+/// queue grow while the engine is down. Once it has passed, messages are read without waiting
+/// until the speakers are initialized again.
+const MAX_WARMUP_WAIT: Duration = Duration::from_secs(10);
 
 pub struct AppState {
-    pub redis_client: redis::Client,
-    pub voicevox_client: VoicevoxClient,
+    pub storage: Arc<dyn Storage>,
+
+    /// The settings that can be replaced at runtime by reloading the config
+    ///
+    /// Never hold the lock across an `.await`; use the accessors below to clone the values out.
+    pub reloadable: RwLock<ReloadableState>,
 
     /// The states of guilds where Koe is connected to a voice channel
-    pub connected_guild_states: DashMap<GuildId, ConnectedGuildState>,
+    ///
+    /// Never hold a reference into this map across an `.await`; use [`AppState::guild_state`] to
+    /// clone the state out of it instead.
+    pub connected_guild_states: DashMap<GuildId, Arc<ConnectedGuildState>>,
+
+    /// Whether the speakers of the current VOICEVOX ENGINE have been initialized
+    ///
+    /// Set it to true with [`AppState::mark_warmed_up`] so that it is not set for a client that
+    /// has been replaced in the meantime.
+    pub warmed_up: watch::Sender<bool>,
+
+    /// Whether [`MAX_WARMUP_WAIT`] has passed since the speakers became uninitialized
+    pub warmup_wait_expired: AtomicBool,
+
+    /// Set once Koe starts shutting down, with how to handle the messages being read at that time
+    pub shutdown: watch::Sender<Option<InFlightPolicy>>,
+}
+
+pub struct ReloadableState {
+    pub voicevox_client: Arc<VoicevoxClient>,
+
+    /// The dictionary applied to all guilds, managed by the operator
+    pub global_dict: Arc<HashMap<String, String>>,
+
+    /// Whether to hold reading messages until [`AppState::warmed_up`] becomes true
+    pub wait_for_warmup: bool,
+
+    pub catchup: CatchupConfig,
+}
+
+impl AppState {
+    pub fn voicevox_client(&self) -> Arc<VoicevoxClient> {
+        Arc::clone(&self.reloadable().voicevox_client)
+    }
+
+    pub fn global_dict(&self) -> Arc<HashMap<String, String>> {
+        Arc::clone(&self.reloadable().global_dict)
+    }
+
+    pub fn catchup(&self) -> CatchupConfig {
+        self.reloadable().catchup
+    }
+
+    /// Marks the speakers as initialized unless `client` is no longer the current client.
+    /// Returns whether it was marked.
+    pub fn mark_warmed_up(&self, client: &Arc<VoicevoxClient>) -> bool {
+        // Holding the lock keeps the client from being replaced until the flag is set
+        let reloadable = self.reloadable();
+        if !Arc::ptr_eq(&reloadable.voicevox_client, client) {
+            return false;
+        }
+
+        self.warmup_wait_expired.store(false, Ordering::Relaxed);
+        self.warmed_up.send_replace(true);
+        true
+    }
+
+    /// Waits until the speakers are initialized if configured to do so.
+    ///
+    /// Gives up after [`MAX_WARMUP_WAIT`], and then does not wait until the speakers are
+    /// initialized again.
+    pub async fn wait_for_warmup(&self) {
+        if !self.reloadable().wait_for_warmup || self.warmup_wait_expired.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut warmed_up = self.warmed_up.subscribe();
+        // An error means the sender has been dropped, which never happens while `self` is alive
+        let wait = warmed_up.wait_for(|warmed_up| *warmed_up);
+        if tokio::time::timeout(MAX_WARMUP_WAIT, wait).await.is_err()
+            && !self.warmup_wait_expired.swap(true, Ordering::Relaxed)
+        {
+            info!(
+                "Speakers are not initialized after {} seconds; reading without waiting until \
+                 they are",
+                MAX_WARMUP_WAIT.as_secs()
+            );
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.borrow().is_some()
+    }
+
+    fn reloadable(&self) -> RwLockReadGuard<'_, ReloadableState> {
+        // The values are replaced as a whole, so the state is consistent even if poisoned
+        self.reloadable
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the state of the guild where Koe is connected to a voice channel.
+    /// The lock on [`AppState::connected_guild_states`] is released before this function returns.
+    pub fn guild_state(&self, guild_id: GuildId) -> Option<Arc<ConnectedGuildState>> {
+        self.connected_guild_states
+            .get(&guild_id)
+            .map(|entry| Arc::clone(entry.value()))
+    }
 }
 
 pub struct ConnectedGuildState {
     /// The text channel where Koe is bound to read messages
     pub bound_text_channel: ChannelId,
 
-    /// The last message that was read aloud in this guild
-    pub last_message_read: Option<Message>,
+    /// The queue of messages waiting to be read by the reader task of this guild
+    ///
+    /// The reader task stops once this sender is dropped, i.e. when the state is removed from
+    /// [`AppState::connected_guild_states`] and all clones of it are dropped.
+    pub message_sender: mpsc::UnboundedSender<QueuedMessage>,
 }
 
 impl TypeMapKey for AppState {
     type Value = Arc<AppState>;
 }
 
-pub async fn initialize(client: &Client, state: AppState) {
+pub async fn initialize(client: &Client, state: AppState) -> Arc<AppState> {
+    let state = Arc::new(state);
+
     let mut data = client.data.write().await;
-    data.insert::<AppState>(Arc::new(state));
+    data.insert::<AppState>(Arc::clone(&state));
+
+    state
 }
 
 pub async fn get(ctx: &Context) -> Result<Arc<AppState>> {
@@ -0,0 +1,94 @@
+use anyhow::{Context as _, Result, bail};
+
+use crate::config::StorageBackend;
+
+const USAGE: &str = "\
+Usage:
+  koe                      Start the bot
+  koe migrate [--dry-run]  Apply pending Redis schema migrations
+  koe transfer FROM TO     Copy all data from one storage backend to another (redis or sqlite)
+  koe commands register [--guild [GUILD_ID]]
+                           Register slash commands globally, or in every guild or the given guild
+  koe commands clear [--guild [GUILD_ID]]
+                           Remove the registered slash commands in the same way";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Start the bot
+    Run,
+    /// Apply pending Redis schema migrations
+    Migrate { dry_run: bool },
+    /// Copy all data from one storage backend to another
+    Transfer {
+        from: StorageBackend,
+        to: StorageBackend,
+    },
+    /// Register or remove slash commands
+    Commands {
+        action: CommandsAction,
+        target: CommandsTarget,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandsAction {
+    Register,
+    Clear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandsTarget {
+    Global,
+    /// Every guild Koe is in
+    AllGuilds,
+    Guild(u64),
+}
+
+pub fn parse() -> Result<Command> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let command = match args[..] {
+        [] => Command::Run,
+        ["migrate"] => Command::Migrate { dry_run: false },
+        ["migrate", "--dry-run"] => Command::Migrate { dry_run: true },
+        ["transfer", from, to] => {
+            let (from, to) = (
+                parse_persistent_backend(from)?,
+                parse_persistent_backend(to)?,
+            );
+            if from == to {
+                bail!("Cannot transfer data from {from} to itself");
+            }
+            Command::Transfer { from, to }
+        }
+        ["commands", action, ref target @ ..] => Command::Commands {
+            action: match action {
+                "register" => CommandsAction::Register,
+                "clear" => CommandsAction::Clear,
+                _ => bail!("Unknown commands action: {action}\n\n{USAGE}"),
+            },
+            target: match target {
+                [] => CommandsTarget::Global,
+                ["--guild"] => CommandsTarget::AllGuilds,
+                ["--guild", guild_id] => CommandsTarget::Guild(
+                    guild_id
+                        .parse()
+                        .with_context(|| format!("Invalid guild ID: {guild_id}"))?,
+                ),
+                _ => bail!("Invalid arguments: {args:?}\n\n{USAGE}"),
+            },
+        },
+        _ => bail!("Invalid arguments: {args:?}\n\n{USAGE}"),
+    };
+
+    Ok(command)
+}
+
+fn parse_persistent_backend(name: &str) -> Result<StorageBackend> {
+    match name {
+        "redis" => Ok(StorageBackend::Redis),
+        "sqlite" => Ok(StorageBackend::Sqlite),
+        _ => bail!("Unknown storage backend: {name} (expected redis or sqlite)\n\n{USAGE}"),
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context as _, Result};
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::application::{CommandInteraction, InteractionContext},
+};
+
+use super::respond_text;
+use crate::voice_call;
+
+const COMMAND_NAME: &str = "clear";
+const ALIAS_COMMAND_NAME: &str = "kclear";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("読み上げ待ちのメッセージをすべてスキップ")
+            .contexts(vec![InteractionContext::Guild]),
+        CreateCommand::new(ALIAS_COMMAND_NAME)
+            .description("読み上げ待ちのメッセージをすべてスキップ")
+            .contexts(vec![InteractionContext::Guild]),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    matches!(cmd.data.name.as_str(), COMMAND_NAME | ALIAS_COMMAND_NAME)
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+
+    if !voice_call::is_connected(ctx, guild_id).await? {
+        respond_text(ctx, cmd, "どのボイスチャンネルにも接続していません。").await?;
+        return Ok(());
+    }
+
+    let count = voice_call::clear(ctx, guild_id).await?;
+
+    respond_text(
+        ctx,
+        cmd,
+        format!("{count}件のメッセージをスキップしました。"),
+    )
+    .await?;
+    Ok(())
+}
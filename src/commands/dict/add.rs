@@ -1,17 +1,15 @@
-use anyhow::{Context as _, Result, bail};
+use anyhow::{Result, bail};
 use serenity::{
     builder::CreateCommandOption,
     client::Context,
     model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
 };
 
-use super::super::{respond_text, sanitize_response};
+use super::{super::respond_text, find_string_option, parse_scope, scope_label, scope_option};
 use crate::{
     app_state,
-    db::{
-        self,
-        dict::{InsertOption, InsertResponse},
-    },
+    db::dict::{InsertOption, InsertResponse},
+    util::sanitize_response,
 };
 
 const SUBCOMMAND_NAME: &str = "add";
@@ -40,6 +38,7 @@ pub fn subcommand() -> CreateCommandOption {
         )
         .required(true),
     )
+    .add_sub_option(scope_option())
 }
 
 pub fn matches(option: &ResolvedOption<'_>) -> bool {
@@ -51,54 +50,39 @@ pub async fn handle(
     cmd: &CommandInteraction,
     option: &ResolvedOption<'_>,
 ) -> Result<()> {
-    let guild_id = cmd
-        .guild_id
-        .context("Guild ID not available in interaction")?;
     let ResolvedValue::SubCommand(suboptions) = &option.value else {
         bail!("Invalid subcommand value for /dict add");
     };
 
-    let [
-        ResolvedOption {
-            name: WORD_OPTION_NAME,
-            value: ResolvedValue::String(word),
-            ..
-        },
-        ResolvedOption {
-            name: READ_AS_OPTION_NAME,
-            value: ResolvedValue::String(read_as),
-            ..
-        },
-    ] = &suboptions[..]
-    else {
+    let (Some(word), Some(read_as)) = (
+        find_string_option(suboptions, WORD_OPTION_NAME),
+        find_string_option(suboptions, READ_AS_OPTION_NAME),
+    ) else {
         bail!("Failed to parse /dict add options");
     };
+    let scope = parse_scope(cmd, suboptions)?;
 
     let state = app_state::get(ctx).await?;
-    let mut conn = state
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await?;
-
-    let resp = db::dict::insert(
-        &mut conn,
-        InsertOption {
-            guild_id: guild_id.into(),
+    let resp = state
+        .storage
+        .insert_dict_word(InsertOption {
+            scope,
             word: word.to_string(),
             read_as: read_as.to_string(),
-        },
-    )
-    .await?;
+        })
+        .await?;
 
     let msg = match resp {
         InsertResponse::Success => format!(
-            "{}の読み方を{}として辞書に登録しました。",
+            "{}の読み方を{}として{}に登録しました。",
             sanitize_response(word),
-            sanitize_response(read_as)
+            sanitize_response(read_as),
+            scope_label(scope)
         ),
         InsertResponse::WordAlreadyExists => format!(
-            "すでに{}は辞書に登録されています。",
-            sanitize_response(word)
+            "すでに{}は{}に登録されています。読み方を変更するには`/dict edit`を使用してください。",
+            sanitize_response(word),
+            scope_label(scope)
         ),
     };
     respond_text(ctx, cmd, msg).await?;
@@ -0,0 +1,91 @@
+use anyhow::{Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::{super::respond_text, find_string_option, parse_scope, scope_label, scope_option};
+use crate::{
+    app_state,
+    db::dict::{UpdateOption, UpdateResponse},
+    util::sanitize_response,
+};
+
+const SUBCOMMAND_NAME: &str = "edit";
+pub const WORD_OPTION_NAME: &str = "word";
+const READ_AS_OPTION_NAME: &str = "read_as";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "辞書に登録されている語句の読み方を変更",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            WORD_OPTION_NAME,
+            "読み方を変更したい語句",
+        )
+        .required(true)
+        .set_autocomplete(true),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            READ_AS_OPTION_NAME,
+            "語句の新しい読み方",
+        )
+        .required(true),
+    )
+    .add_sub_option(scope_option())
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /dict edit");
+    };
+
+    let (Some(word), Some(read_as)) = (
+        find_string_option(suboptions, WORD_OPTION_NAME),
+        find_string_option(suboptions, READ_AS_OPTION_NAME),
+    ) else {
+        bail!("Failed to parse /dict edit options");
+    };
+    let scope = parse_scope(cmd, suboptions)?;
+
+    let state = app_state::get(ctx).await?;
+    let resp = state
+        .storage
+        .update_dict_word(UpdateOption {
+            scope,
+            word: word.to_string(),
+            read_as: read_as.to_string(),
+        })
+        .await?;
+
+    let msg = match resp {
+        UpdateResponse::Success => format!(
+            "{}の{}の読み方を{}に変更しました。",
+            scope_label(scope),
+            sanitize_response(word),
+            sanitize_response(read_as)
+        ),
+        UpdateResponse::WordDoesNotExist => format!(
+            "{}は{}に登録されていません。",
+            sanitize_response(word),
+            scope_label(scope)
+        ),
+    };
+    respond_text(ctx, cmd, msg).await?;
+    Ok(())
+}
@@ -1,15 +1,29 @@
 pub mod add;
+pub mod edit;
 pub mod remove;
 pub mod view;
 
 use anyhow::{Context as _, Ok, Result, bail};
 use serenity::{
-    builder::CreateCommand,
+    builder::{
+        AutocompleteChoice, CreateAutocompleteResponse, CreateCommand, CreateCommandOption,
+        CreateInteractionResponse,
+    },
     client::Context as SerenityContext,
-    model::application::{CommandInteraction, InteractionContext},
+    model::application::{
+        CommandInteraction, CommandOptionType, InteractionContext, ResolvedOption, ResolvedValue,
+    },
+};
+
+use crate::{
+    app_state,
+    db::dict::{DictScope, GetAllOption},
 };
 
 const COMMAND_NAME: &str = "dict";
+const SCOPE_OPTION_NAME: &str = "scope";
+const SCOPE_GUILD: &str = "guild";
+const SCOPE_USER: &str = "user";
 
 pub fn commands() -> Vec<CreateCommand> {
     vec![
@@ -17,6 +31,7 @@ pub fn commands() -> Vec<CreateCommand> {
             .description("読み上げ辞書の閲覧と編集")
             .contexts(vec![InteractionContext::Guild])
             .add_option(add::subcommand())
+            .add_option(edit::subcommand())
             .add_option(remove::subcommand())
             .add_option(view::subcommand()),
     ]
@@ -36,12 +51,16 @@ pub async fn handle(ctx: &SerenityContext, cmd: &CommandInteraction) -> Result<(
         add::handle(ctx, cmd, option)
             .await
             .context("Failed to execute /dict add")?;
+    } else if edit::matches(option) {
+        edit::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /dict edit")?;
     } else if remove::matches(option) {
         remove::handle(ctx, cmd, option)
             .await
             .context("Failed to execute /dict remove")?;
     } else if view::matches(option) {
-        view::handle(ctx, cmd)
+        view::handle(ctx, cmd, option)
             .await
             .context("Failed to execute /dict view")?;
     } else {
@@ -50,3 +69,112 @@ pub async fn handle(ctx: &SerenityContext, cmd: &CommandInteraction) -> Result<(
 
     Ok(())
 }
+
+pub async fn handle_autocomplete(ctx: &SerenityContext, cmd: &CommandInteraction) -> Result<()> {
+    let options = cmd.data.options();
+    let Some(option) = options.first() else {
+        bail!("No subcommand provided for /dict");
+    };
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /dict {}", option.name);
+    };
+    let focused = cmd
+        .data
+        .autocomplete()
+        .context("Focused option not available in autocomplete interaction")?;
+
+    let is_word_option = (remove::matches(option) && focused.name == remove::WORD_OPTION_NAME)
+        || (edit::matches(option) && focused.name == edit::WORD_OPTION_NAME);
+    let choices = if is_word_option {
+        let scope = parse_scope(cmd, suboptions)?;
+        list_word_choices(ctx, scope, focused.value).await?
+    } else {
+        Vec::new()
+    };
+
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Autocomplete(
+            CreateAutocompleteResponse::new().set_choices(choices),
+        ),
+    )
+    .await
+    .context("Failed to create autocomplete response")?;
+
+    Ok(())
+}
+
+/// Helper function to list the words in the dictionary that match the input for autocomplete.
+/// Words starting with the input come first.
+async fn list_word_choices(
+    ctx: &SerenityContext,
+    scope: DictScope,
+    input: &str,
+) -> Result<Vec<AutocompleteChoice>> {
+    const MAX_CHOICES: usize = 25;
+    const MAX_CHOICE_CHARS: usize = 100;
+
+    let state = app_state::get(ctx).await?;
+    let dict = state.storage.get_dict(GetAllOption { scope }).await?;
+
+    let mut words = dict
+        .into_iter()
+        .map(|(word, _)| word)
+        .filter(|word| word.contains(input) && word.chars().count() <= MAX_CHOICE_CHARS)
+        .collect::<Vec<_>>();
+    words.sort_by(|a, b| (!a.starts_with(input), a).cmp(&(!b.starts_with(input), b)));
+
+    let choices = words
+        .into_iter()
+        .take(MAX_CHOICES)
+        .map(|word| AutocompleteChoice::new(word.clone(), word))
+        .collect();
+
+    Ok(choices)
+}
+
+/// Helper function to create the option to choose which dictionary to use
+fn scope_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        SCOPE_OPTION_NAME,
+        "対象の辞書（省略時はサーバーの辞書）",
+    )
+    .add_string_choice("サーバーの辞書", SCOPE_GUILD)
+    .add_string_choice("自分専用の辞書", SCOPE_USER)
+}
+
+/// Helper function to parse the option to choose which dictionary to use
+fn parse_scope(cmd: &CommandInteraction, suboptions: &[ResolvedOption<'_>]) -> Result<DictScope> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?
+        .get();
+
+    let scope = match find_string_option(suboptions, SCOPE_OPTION_NAME) {
+        None | Some(SCOPE_GUILD) => DictScope::Guild { guild_id },
+        Some(SCOPE_USER) => DictScope::User {
+            guild_id,
+            user_id: cmd.user.id.get(),
+        },
+        Some(x) => bail!("Unknown dictionary scope: {x}"),
+    };
+
+    Ok(scope)
+}
+
+/// Helper function to describe the dictionary in responses
+fn scope_label(scope: DictScope) -> &'static str {
+    match scope {
+        DictScope::Guild { .. } => "サーバーの辞書",
+        DictScope::User { .. } => "あなた専用の辞書",
+    }
+}
+
+/// Helper function to find the value of a string option by its name
+fn find_string_option<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|option| match option.value {
+        ResolvedValue::String(value) if option.name == name => Some(value),
+        _ => None,
+    })
+}
@@ -1,18 +1,19 @@
-use anyhow::{Context as _, Result, bail};
+use anyhow::{Result, bail};
 use serenity::{
     builder::CreateCommandOption,
     client::Context,
     model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
 };
 
-use super::super::{respond_text, sanitize_response};
+use super::{super::respond_text, find_string_option, parse_scope, scope_label, scope_option};
 use crate::{
     app_state,
     db::{self, dict::RemoveResponse},
+    util::sanitize_response,
 };
 
 const SUBCOMMAND_NAME: &str = "remove";
-const WORD_OPTION_NAME: &str = "word";
+pub const WORD_OPTION_NAME: &str = "word";
 
 pub fn subcommand() -> CreateCommandOption {
     CreateCommandOption::new(
@@ -26,8 +27,10 @@ pub fn subcommand() -> CreateCommandOption {
             WORD_OPTION_NAME,
             "削除したい語句",
         )
-        .required(true),
+        .required(true)
+        .set_autocomplete(true),
     )
+    .add_sub_option(scope_option())
 }
 
 pub fn matches(option: &ResolvedOption<'_>) -> bool {
@@ -39,44 +42,35 @@ pub async fn handle(
     cmd: &CommandInteraction,
     option: &ResolvedOption<'_>,
 ) -> Result<()> {
-    let guild_id = cmd
-        .guild_id
-        .context("Guild ID not available in interaction")?;
     let ResolvedValue::SubCommand(suboptions) = &option.value else {
         bail!("Invalid subcommand value for /dict remove");
     };
 
-    let [
-        ResolvedOption {
-            name: WORD_OPTION_NAME,
-            value: ResolvedValue::String(word),
-            ..
-        },
-    ] = &suboptions[..]
-    else {
+    let Some(word) = find_string_option(suboptions, WORD_OPTION_NAME) else {
         bail!("Failed to parse /dict remove options");
     };
+    let scope = parse_scope(cmd, suboptions)?;
 
     let state = app_state::get(ctx).await?;
-    let mut conn = state
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await?;
-
-    let resp = db::dict::remove(
-        &mut conn,
-        db::dict::RemoveOption {
-            guild_id: guild_id.into(),
+    let resp = state
+        .storage
+        .remove_dict_word(db::dict::RemoveOption {
+            scope,
             word: word.to_string(),
-        },
-    )
-    .await?;
+        })
+        .await?;
 
     let msg = match resp {
-        RemoveResponse::Success => format!("辞書から{}を削除しました。", sanitize_response(word)),
-        RemoveResponse::WordDoesNotExist => {
-            format!("{}は辞書に登録されていません。", sanitize_response(word))
-        }
+        RemoveResponse::Success => format!(
+            "{}から{}を削除しました。",
+            scope_label(scope),
+            sanitize_response(word)
+        ),
+        RemoveResponse::WordDoesNotExist => format!(
+            "{}は{}に登録されていません。",
+            sanitize_response(word),
+            scope_label(scope)
+        ),
     };
     respond_text(ctx, cmd, msg).await?;
     Ok(())
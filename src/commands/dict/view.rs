@@ -1,68 +1,81 @@
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, bail};
 use serenity::{
-    builder::{
-        CreateCommandOption, CreateEmbed, CreateInteractionResponse,
-        CreateInteractionResponseMessage,
-    },
+    builder::{CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage},
     client::Context,
-    model::application::{CommandInteraction, CommandOptionType, ResolvedOption},
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
 };
 
-use super::super::sanitize_response;
-use crate::{
-    app_state,
-    db::{self, dict::GetAllOption},
-};
+use super::{find_string_option, parse_scope, scope_option};
+use crate::components::dict_view::{self, DictView, MAX_QUERY_CHARS, SortOrder};
 
 const SUBCOMMAND_NAME: &str = "view";
+const QUERY_OPTION_NAME: &str = "query";
+const SORT_OPTION_NAME: &str = "sort";
+const SORT_WORD: &str = "word";
+const SORT_READ_AS: &str = "read_as";
 
 pub fn subcommand() -> CreateCommandOption {
     CreateCommandOption::new(CommandOptionType::SubCommand, SUBCOMMAND_NAME, "辞書を表示")
+        .add_sub_option(scope_option())
+        .add_sub_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                QUERY_OPTION_NAME,
+                "語句または読み方に含まれる文字列で絞り込む",
+            )
+            .max_length(MAX_QUERY_CHARS),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                SORT_OPTION_NAME,
+                "並び順（省略時は語句順）",
+            )
+            .add_string_choice("語句順", SORT_WORD)
+            .add_string_choice("読み方順", SORT_READ_AS),
+        )
 }
 
 pub fn matches(option: &ResolvedOption<'_>) -> bool {
     option.name == SUBCOMMAND_NAME
 }
 
-pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
     let guild_id = cmd
         .guild_id
         .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /dict view");
+    };
 
-    let state = app_state::get(ctx).await?;
-    let mut conn = state
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await?;
-
-    let dict = db::dict::get_all(
-        &mut conn,
-        GetAllOption {
-            guild_id: guild_id.into(),
-        },
-    )
-    .await?;
-
-    {
-        let mut embed = CreateEmbed::default();
-
-        let guild_name = guild_id
-            .name(&ctx.cache)
-            .unwrap_or_else(|| "サーバー".to_string());
+    let sort = match find_string_option(suboptions, SORT_OPTION_NAME) {
+        None | Some(SORT_WORD) => SortOrder::Word,
+        Some(SORT_READ_AS) => SortOrder::ReadAs,
+        Some(x) => bail!("Unknown sort order: {x}"),
+    };
 
-        embed = embed.title(format!("📕 {guild_name}の辞書"));
+    let view = DictView {
+        scope: parse_scope(cmd, suboptions)?,
+        query: find_string_option(suboptions, QUERY_OPTION_NAME)
+            .unwrap_or_default()
+            .to_string(),
+        sort,
+        page_idx: 0,
+    };
 
-        embed = embed.fields(
-            dict.into_iter()
-                .map(|(word, read_as)| (word, sanitize_response(&read_as), false)),
-        );
+    let (embed, components) = dict_view::render(ctx, guild_id, &view).await?;
 
-        let message = CreateInteractionResponseMessage::new().embed(embed);
+    let message = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components);
 
-        cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
-            .await
-            .context("Failed to create interaction response")?;
-    };
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
 
     Ok(())
 }
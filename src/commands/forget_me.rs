@@ -0,0 +1,44 @@
+use anyhow::Result;
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::application::{CommandInteraction, InteractionContext},
+};
+
+use super::respond_text_ephemeral;
+use crate::{app_state, db::user::DeleteGuildDataOption};
+
+const COMMAND_NAME: &str = "forget-me";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("すべてのサーバーに保存されている自分の設定を削除")
+            .contexts(vec![InteractionContext::Guild]),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    cmd.data.name == COMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let state = app_state::get(ctx).await?;
+    let count = state
+        .storage
+        .delete_guild_user_data(DeleteGuildDataOption {
+            user_id: cmd.user.id.into(),
+        })
+        .await?;
+
+    let msg = if count == 0 {
+        "保存されているあなたの設定はありません。".to_string()
+    } else {
+        format!(
+            "すべてのサーバーに保存されていたあなたの設定（{count}件）を削除しました。\n-# \
+             `/readme off`で設定した読み上げの拒否は削除されません。"
+        )
+    };
+    respond_text_ephemeral(ctx, cmd, msg).await?;
+    Ok(())
+}
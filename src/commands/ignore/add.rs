@@ -0,0 +1,85 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::{super::respond_text_without_mentions, TARGET_OPTION_NAME, mention, parse_target};
+use crate::{
+    app_state,
+    db::ignore::{InsertOption, InsertResponse},
+};
+
+const SUBCOMMAND_NAME: &str = "add";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "メッセージを読み上げないメンバーまたはロールを追加",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Mentionable,
+            TARGET_OPTION_NAME,
+            "読み上げないメンバーまたはロール",
+        )
+        .required(true),
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /ignore add");
+    };
+
+    let [
+        ResolvedOption {
+            name: TARGET_OPTION_NAME,
+            value,
+            ..
+        },
+    ] = &suboptions[..]
+    else {
+        bail!("Failed to parse /ignore add options");
+    };
+    let target = parse_target(value).context("Failed to parse /ignore add target")?;
+
+    let state = app_state::get(ctx).await?;
+    let resp = state
+        .storage
+        .insert_ignore_target(InsertOption {
+            guild_id: guild_id.into(),
+            target,
+        })
+        .await?;
+
+    let msg = match resp {
+        InsertResponse::Success => {
+            format!(
+                "{}のメッセージを読み上げないようにしました。",
+                mention(target)
+            )
+        }
+        InsertResponse::AlreadyIgnored => {
+            format!(
+                "すでに{}のメッセージは読み上げない設定です。",
+                mention(target)
+            )
+        }
+    };
+    respond_text_without_mentions(ctx, cmd, msg).await?;
+    Ok(())
+}
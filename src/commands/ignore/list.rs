@@ -0,0 +1,64 @@
+use anyhow::{Context as _, Result};
+use serenity::{
+    builder::{
+        CreateAllowedMentions, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption},
+};
+
+use super::mention;
+use crate::{app_state, db::ignore::GetAllOption};
+
+const SUBCOMMAND_NAME: &str = "list";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "メッセージを読み上げないメンバーとロールを表示",
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+
+    let state = app_state::get(ctx).await?;
+    let ignore_list = state
+        .storage
+        .get_ignore_targets(GetAllOption {
+            guild_id: guild_id.into(),
+        })
+        .await?;
+
+    let description = if ignore_list.is_empty() {
+        "設定されていません。".to_string()
+    } else {
+        ignore_list
+            .into_iter()
+            .map(|target| format!("- {}", mention(target)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::default()
+        .title("🔇 読み上げないメンバーとロール")
+        .description(description);
+
+    let message = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+pub mod add;
+pub mod list;
+pub mod remove;
+
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::{
+        application::{CommandInteraction, InteractionContext, ResolvedValue},
+        permissions::Permissions,
+    },
+};
+
+use crate::db::ignore::IgnoreTarget;
+
+const COMMAND_NAME: &str = "ignore";
+const TARGET_OPTION_NAME: &str = "target";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("読み上げないメンバーやロールの設定")
+            .contexts(vec![InteractionContext::Guild])
+            .default_member_permissions(Permissions::MANAGE_GUILD)
+            .add_option(add::subcommand())
+            .add_option(remove::subcommand())
+            .add_option(list::subcommand()),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    cmd.data.name == COMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let options = cmd.data.options();
+    let Some(option) = options.first() else {
+        bail!("No subcommand provided for /ignore");
+    };
+
+    if add::matches(option) {
+        add::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /ignore add")?;
+    } else if remove::matches(option) {
+        remove::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /ignore remove")?;
+    } else if list::matches(option) {
+        list::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /ignore list")?;
+    } else {
+        bail!("Unknown subcommand for /ignore: {}", option.name);
+    }
+
+    Ok(())
+}
+
+/// Helper function to convert the value of a mentionable option into [`IgnoreTarget`]
+fn parse_target(value: &ResolvedValue<'_>) -> Option<IgnoreTarget> {
+    match value {
+        ResolvedValue::User(user, _) => Some(IgnoreTarget::User(user.id.get())),
+        ResolvedValue::Role(role) => Some(IgnoreTarget::Role(role.id.get())),
+        _ => None,
+    }
+}
+
+/// Helper function to format [`IgnoreTarget`] as a mention
+fn mention(target: IgnoreTarget) -> String {
+    match target {
+        IgnoreTarget::User(user_id) => format!("<@{user_id}>"),
+        IgnoreTarget::Role(role_id) => format!("<@&{role_id}>"),
+    }
+}
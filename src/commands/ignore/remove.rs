@@ -0,0 +1,85 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::{super::respond_text_without_mentions, TARGET_OPTION_NAME, mention, parse_target};
+use crate::{
+    app_state,
+    db::ignore::{RemoveOption, RemoveResponse},
+};
+
+const SUBCOMMAND_NAME: &str = "remove";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "メッセージを読み上げないメンバーまたはロールを削除",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Mentionable,
+            TARGET_OPTION_NAME,
+            "再び読み上げるメンバーまたはロール",
+        )
+        .required(true),
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /ignore remove");
+    };
+
+    let [
+        ResolvedOption {
+            name: TARGET_OPTION_NAME,
+            value,
+            ..
+        },
+    ] = &suboptions[..]
+    else {
+        bail!("Failed to parse /ignore remove options");
+    };
+    let target = parse_target(value).context("Failed to parse /ignore remove target")?;
+
+    let state = app_state::get(ctx).await?;
+    let resp = state
+        .storage
+        .remove_ignore_target(RemoveOption {
+            guild_id: guild_id.into(),
+            target,
+        })
+        .await?;
+
+    let msg = match resp {
+        RemoveResponse::Success => {
+            format!(
+                "{}のメッセージを再び読み上げるようにしました。",
+                mention(target)
+            )
+        }
+        RemoveResponse::NotIgnored => {
+            format!(
+                "{}のメッセージは読み上げない設定になっていません。",
+                mention(target)
+            )
+        }
+    };
+    respond_text_without_mentions(ctx, cmd, msg).await?;
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::{Context as _, Result};
 use serenity::{
     builder::CreateCommand,
@@ -9,7 +11,7 @@ use serenity::{
 };
 
 use super::respond_text;
-use crate::{app_state, voice_call};
+use crate::{app_state, message, voice_call};
 
 const COMMAND_NAME: &str = "join";
 const ALIAS_COMMAND_NAME: &str = "kjoin";
@@ -51,10 +53,10 @@ pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
     let state = app_state::get(ctx).await?;
     state.connected_guild_states.insert(
         guild_id,
-        app_state::ConnectedGuildState {
+        Arc::new(app_state::ConnectedGuildState {
             bound_text_channel: text_channel_id,
-            last_message_read: None,
-        },
+            message_sender: message::spawn_reader(ctx.clone(), &state, guild_id),
+        }),
     );
 
     respond_text(
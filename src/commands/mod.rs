@@ -1,13 +1,24 @@
+mod clear;
 mod dict;
+mod forget_me;
 mod help;
+mod ignore;
 mod join;
 mod leave;
+mod name;
+mod queue;
+mod readme;
+pub mod registration;
+mod setting;
 mod skip;
 mod voice;
 
 use anyhow::{Context as _, Result, bail};
 use serenity::{
-    builder::{CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage},
+    builder::{
+        CreateAllowedMentions, CreateCommand, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
     client::Context,
     model::application::CommandInteraction,
 };
@@ -15,10 +26,17 @@ use serenity::{
 pub fn commands() -> Vec<CreateCommand> {
     let mut commands = Vec::new();
 
+    commands.extend(clear::commands());
     commands.extend(dict::commands());
+    commands.extend(forget_me::commands());
     commands.extend(help::commands());
+    commands.extend(ignore::commands());
     commands.extend(join::commands());
     commands.extend(leave::commands());
+    commands.extend(name::commands());
+    commands.extend(queue::commands());
+    commands.extend(readme::commands());
+    commands.extend(setting::commands());
     commands.extend(skip::commands());
     commands.extend(voice::commands());
 
@@ -26,14 +44,26 @@ pub fn commands() -> Vec<CreateCommand> {
 }
 
 pub async fn handle_interaction(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
-    if dict::matches(cmd) {
+    if clear::matches(cmd) {
+        clear::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /clear")?;
+    } else if dict::matches(cmd) {
         dict::handle(ctx, cmd)
             .await
             .context("Failed to execute /dict")?;
+    } else if forget_me::matches(cmd) {
+        forget_me::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /forget-me")?;
     } else if help::matches(cmd) {
         help::handle(ctx, cmd)
             .await
             .context("Failed to execute /help")?;
+    } else if ignore::matches(cmd) {
+        ignore::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /ignore")?;
     } else if join::matches(cmd) {
         join::handle(ctx, cmd)
             .await
@@ -42,6 +72,22 @@ pub async fn handle_interaction(ctx: &Context, cmd: &CommandInteraction) -> Resu
         leave::handle(ctx, cmd)
             .await
             .context("Failed to execute /leave")?;
+    } else if name::matches(cmd) {
+        name::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /name")?;
+    } else if queue::matches(cmd) {
+        queue::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /queue")?;
+    } else if readme::matches(cmd) {
+        readme::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /readme")?;
+    } else if setting::matches(cmd) {
+        setting::handle(ctx, cmd)
+            .await
+            .context("Failed to execute /setting")?;
     } else if skip::matches(cmd) {
         skip::handle(ctx, cmd)
             .await
@@ -57,6 +103,28 @@ pub async fn handle_interaction(ctx: &Context, cmd: &CommandInteraction) -> Resu
     Ok(())
 }
 
+pub async fn handle_autocomplete(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    if dict::matches(cmd) {
+        dict::handle_autocomplete(ctx, cmd)
+            .await
+            .context("Failed to autocomplete /dict")?;
+    } else {
+        bail!("Unknown command for autocomplete: {:?}", cmd.data.name);
+    }
+
+    Ok(())
+}
+
+/// The message shown when a command fails because the storage is unavailable
+pub const STORAGE_UNAVAILABLE_MESSAGE: &str = "設定の保存先に接続できないため、\
+                                               コマンドを実行できませんでした。\
+                                               しばらくしてから再度お試しください。";
+
+/// Notifies the user who invoked the command that it failed because the storage is unavailable
+pub async fn respond_storage_unavailable(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    respond_text_ephemeral(ctx, cmd, STORAGE_UNAVAILABLE_MESSAGE).await
+}
+
 /// Helper function to create text message response
 async fn respond_text(
     ctx: &Context,
@@ -72,7 +140,36 @@ async fn respond_text(
     Ok(())
 }
 
-/// Helper function to sanitize text for Discord responses
-fn sanitize_response(text: &str) -> String {
-    format!("`{}`", text.replace('`', ""))
+/// Helper function to create text message response visible only to the user who invoked the command
+async fn respond_text_ephemeral(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    text: impl Into<String>,
+) -> Result<()> {
+    let message = CreateInteractionResponseMessage::new()
+        .content(text)
+        .ephemeral(true);
+
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
+
+/// Helper function to create text message response without notifying mentioned users and roles
+async fn respond_text_without_mentions(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    text: impl Into<String>,
+) -> Result<()> {
+    let message = CreateInteractionResponseMessage::new()
+        .content(text)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
 }
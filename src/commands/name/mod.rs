@@ -0,0 +1,76 @@
+pub mod reset;
+pub mod set;
+
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::{
+        application::{CommandInteraction, InteractionContext, ResolvedOption, ResolvedValue},
+        id::UserId,
+        permissions::Permissions,
+    },
+};
+
+const COMMAND_NAME: &str = "name";
+const USER_OPTION_NAME: &str = "user";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("名前の読み方の設定")
+            .contexts(vec![InteractionContext::Guild])
+            .add_option(set::subcommand())
+            .add_option(reset::subcommand()),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    cmd.data.name == COMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let options = cmd.data.options();
+    let Some(option) = options.first() else {
+        bail!("No subcommand provided for /name");
+    };
+
+    if set::matches(option) {
+        set::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /name set")?;
+    } else if reset::matches(option) {
+        reset::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /name reset")?;
+    } else {
+        bail!("Unknown subcommand for /name: {}", option.name);
+    }
+
+    Ok(())
+}
+
+/// Helper function to determine whose name reading is changed by the command.
+/// Returns [`None`] if the invoker is not allowed to change the reading of the specified user.
+fn target_user(cmd: &CommandInteraction, suboptions: &[ResolvedOption<'_>]) -> Option<UserId> {
+    let specified_user = suboptions.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: USER_OPTION_NAME,
+            value: ResolvedValue::User(user, _),
+            ..
+        } => Some(user.id),
+        _ => None,
+    });
+
+    let Some(user_id) = specified_user.filter(|id| *id != cmd.user.id) else {
+        return Some(cmd.user.id);
+    };
+
+    let can_manage_nicknames = cmd
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(Permissions::manage_nicknames);
+
+    can_manage_nicknames.then_some(user_id)
+}
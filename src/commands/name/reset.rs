@@ -0,0 +1,75 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::{
+    super::{respond_text, respond_text_without_mentions},
+    USER_OPTION_NAME, target_user,
+};
+use crate::{
+    app_state,
+    db::name::{RemoveOption, RemoveResponse},
+};
+
+const SUBCOMMAND_NAME: &str = "reset";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "名前の読み方の設定を削除",
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::User,
+        USER_OPTION_NAME,
+        "設定を削除するメンバー（他のメンバーを指定するにはニックネームの管理権限が必要）",
+    ))
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /name reset");
+    };
+
+    let Some(user_id) = target_user(cmd, suboptions) else {
+        respond_text(
+            ctx,
+            cmd,
+            "他のメンバーの名前の読み方を削除するにはニックネームの管理権限が必要です。",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let state = app_state::get(ctx).await?;
+    let resp = state
+        .storage
+        .remove_name(RemoveOption {
+            guild_id: guild_id.into(),
+            user_id: user_id.into(),
+        })
+        .await?;
+
+    let msg = match resp {
+        RemoveResponse::Success => format!("<@{user_id}>の名前の読み方の設定を削除しました。"),
+        RemoveResponse::ReadingDoesNotExist => {
+            format!("<@{user_id}>の名前の読み方は設定されていません。")
+        }
+    };
+    respond_text_without_mentions(ctx, cmd, msg).await?;
+    Ok(())
+}
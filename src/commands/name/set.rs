@@ -0,0 +1,101 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::{
+    super::{respond_text, respond_text_without_mentions},
+    USER_OPTION_NAME, target_user,
+};
+use crate::{app_state, db::name::SetOption, util::sanitize_response};
+
+const SUBCOMMAND_NAME: &str = "set";
+const READING_OPTION_NAME: &str = "reading";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "名前の読み方を設定",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            READING_OPTION_NAME,
+            "名前の読み方",
+        )
+        .required(true)
+        .max_length(30),
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::User,
+        USER_OPTION_NAME,
+        "読み方を設定するメンバー（他のメンバーを指定するにはニックネームの管理権限が必要）",
+    ))
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /name set");
+    };
+
+    let Some(reading) = suboptions.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: READING_OPTION_NAME,
+            value: ResolvedValue::String(reading),
+            ..
+        } => Some(reading.trim()),
+        _ => None,
+    }) else {
+        bail!("Failed to parse /name set options");
+    };
+
+    let Some(user_id) = target_user(cmd, suboptions) else {
+        respond_text(
+            ctx,
+            cmd,
+            "他のメンバーの名前の読み方を設定するにはニックネームの管理権限が必要です。",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    if reading.is_empty() {
+        respond_text(ctx, cmd, "読み方を入力してください。").await?;
+        return Ok(());
+    }
+
+    let state = app_state::get(ctx).await?;
+    state
+        .storage
+        .set_name(SetOption {
+            guild_id: guild_id.into(),
+            user_id: user_id.into(),
+            reading: reading.to_string(),
+        })
+        .await?;
+
+    respond_text_without_mentions(
+        ctx,
+        cmd,
+        format!(
+            "<@{user_id}>の名前の読み方を{}に設定しました。",
+            sanitize_response(reading)
+        ),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anyhow::{Context as _, Result};
+use serenity::{
+    builder::{
+        CreateCommand, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::application::{CommandInteraction, InteractionContext},
+};
+
+use super::respond_text;
+use crate::{util::sanitize_response, voice_call};
+
+const COMMAND_NAME: &str = "queue";
+const ALIAS_COMMAND_NAME: &str = "kqueue";
+
+/// 表示するメッセージの最大件数
+const MAX_ITEMS: usize = 15;
+/// メッセージのプレビューの最大文字数
+const MAX_PREVIEW_CHARS: usize = 30;
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("読み上げ待ちのメッセージを表示")
+            .contexts(vec![InteractionContext::Guild]),
+        CreateCommand::new(ALIAS_COMMAND_NAME)
+            .description("読み上げ待ちのメッセージを表示")
+            .contexts(vec![InteractionContext::Guild]),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    matches!(cmd.data.name.as_str(), COMMAND_NAME | ALIAS_COMMAND_NAME)
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+
+    if !voice_call::is_connected(ctx, guild_id).await? {
+        respond_text(ctx, cmd, "どのボイスチャンネルにも接続していません。").await?;
+        return Ok(());
+    }
+
+    let queue = voice_call::list_queue(ctx, guild_id).await?;
+
+    if queue.is_empty() {
+        respond_text(ctx, cmd, "読み上げ待ちのメッセージはありません。").await?;
+        return Ok(());
+    }
+
+    let mut lines = queue
+        .iter()
+        .take(MAX_ITEMS)
+        .enumerate()
+        .map(|(idx, track)| {
+            let position = if idx == 0 {
+                "🔊".to_string()
+            } else {
+                format!("{idx}.")
+            };
+            format!(
+                "{position} **{}**: {}",
+                sanitize_response(&track.author_name),
+                sanitize_response(&preview(&track.text))
+            )
+        })
+        .collect::<Vec<_>>();
+    if queue.len() > MAX_ITEMS {
+        lines.push(format!("他{}件", queue.len() - MAX_ITEMS));
+    }
+
+    let embed = CreateEmbed::default()
+        .title(format!(
+            "📜 読み上げ待ちのメッセージ ({}件)",
+            queue.len() - 1
+        ))
+        .description(lines.join("\n"));
+
+    let message = CreateInteractionResponseMessage::new().embed(embed);
+
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
+
+fn preview(text: &str) -> String {
+    if text.chars().count() > MAX_PREVIEW_CHARS {
+        text.chars().take(MAX_PREVIEW_CHARS - 1).collect::<String>() + "…"
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, InteractionContext},
+};
+
+use super::respond_text_ephemeral;
+use crate::{app_state, db::user::SetOptOutOption};
+
+const COMMAND_NAME: &str = "readme";
+const ON_SUBCOMMAND_NAME: &str = "on";
+const OFF_SUBCOMMAND_NAME: &str = "off";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("自分のメッセージを読み上げるかの設定")
+            .contexts(vec![InteractionContext::Guild])
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                ON_SUBCOMMAND_NAME,
+                "自分のメッセージを読み上げる",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                OFF_SUBCOMMAND_NAME,
+                "すべてのサーバーで自分のメッセージを読み上げない",
+            )),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    cmd.data.name == COMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let options = cmd.data.options();
+    let Some(option) = options.first() else {
+        bail!("No subcommand provided for /readme");
+    };
+
+    let opt_out = match option.name {
+        ON_SUBCOMMAND_NAME => false,
+        OFF_SUBCOMMAND_NAME => true,
+        name => bail!("Unknown subcommand for /readme: {name}"),
+    };
+
+    let state = app_state::get(ctx).await?;
+    state
+        .storage
+        .set_opt_out(SetOptOutOption {
+            user_id: cmd.user.id.into(),
+            value: opt_out,
+        })
+        .await
+        .context("Failed to update opt-out setting")?;
+
+    let msg = if opt_out {
+        "すべてのサーバーであなたのメッセージを読み上げないようにしました。"
+    } else {
+        "あなたのメッセージを読み上げるようにしました。"
+    };
+    respond_text_ephemeral(ctx, cmd, msg).await?;
+    Ok(())
+}
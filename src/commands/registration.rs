@@ -0,0 +1,132 @@
+//! スラッシュコマンドの登録
+
+use anyhow::{Context as _, Result};
+use serenity::{
+    builder::CreateCommand,
+    http::{GuildPagination, Http},
+    model::{
+        application::Command,
+        id::{ApplicationId, GuildId},
+    },
+};
+use tracing::info;
+
+use super::commands;
+use crate::db::Storage;
+
+/// 一度に取得するサーバーの数の上限
+const GUILDS_PER_PAGE: u64 = 200;
+
+/// コマンドの定義が前回グローバルに登録したものから変わっていれば登録し直す
+/// 登録した場合は`true`を返す
+pub async fn register_global_if_changed(http: &Http, storage: &dyn Storage) -> Result<bool> {
+    let commands = commands();
+    let hash = definitions_hash(application_id(http)?, &commands)?;
+
+    if storage.get_command_hash().await?.as_deref() == Some(hash.as_str()) {
+        info!("Global application commands are up to date");
+        return Ok(false);
+    }
+
+    set_global(http, storage, commands, Some(hash)).await?;
+    Ok(true)
+}
+
+/// コマンドをグローバルに登録する
+pub async fn register_global(http: &Http, storage: &dyn Storage) -> Result<()> {
+    let commands = commands();
+    let hash = definitions_hash(application_id(http)?, &commands)?;
+
+    set_global(http, storage, commands, Some(hash)).await
+}
+
+/// グローバルに登録したコマンドをすべて削除する
+pub async fn clear_global(http: &Http, storage: &dyn Storage) -> Result<()> {
+    set_global(http, storage, Vec::new(), None).await
+}
+
+async fn set_global(
+    http: &Http,
+    storage: &dyn Storage,
+    commands: Vec<CreateCommand>,
+    hash: Option<String>,
+) -> Result<()> {
+    let registered = Command::set_global_commands(http, commands)
+        .await
+        .context("Failed to set global application commands")?;
+    info!("Set {} global application commands", registered.len());
+
+    storage
+        .set_command_hash(hash)
+        .await
+        .context("Failed to save hash of application commands")?;
+
+    Ok(())
+}
+
+/// コマンドをサーバーに登録する
+pub async fn register_guild(http: &Http, guild_id: GuildId) -> Result<()> {
+    guild_id
+        .set_commands(http, commands())
+        .await
+        .with_context(|| format!("Failed to set application commands in guild {guild_id}"))?;
+    Ok(())
+}
+
+/// サーバーに登録したコマンドをすべて削除する
+pub async fn clear_guild(http: &Http, guild_id: GuildId) -> Result<()> {
+    guild_id
+        .set_commands(http, Vec::new())
+        .await
+        .with_context(|| format!("Failed to clear application commands in guild {guild_id}"))?;
+    Ok(())
+}
+
+/// Koeが参加しているすべてのサーバーを返す
+pub async fn list_guilds(http: &Http) -> Result<Vec<GuildId>> {
+    let mut guild_ids = Vec::new();
+
+    loop {
+        let target = guild_ids.last().copied().map(GuildPagination::After);
+        let page = http
+            .get_guilds(target, Some(GUILDS_PER_PAGE))
+            .await
+            .context("Failed to list guilds")?;
+        let is_last_page = (page.len() as u64) < GUILDS_PER_PAGE;
+        guild_ids.extend(page.into_iter().map(|guild| guild.id));
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(guild_ids)
+}
+
+fn application_id(http: &Http) -> Result<ApplicationId> {
+    http.application_id()
+        .context("Application ID is required to register application commands")
+}
+
+/// コマンドを登録するアプリケーションとコマンドの定義のハッシュを返す
+///
+/// 定義の変更を検出するためのもので、暗号学的な強度は必要ないためFNV-1aを使う。
+/// [`std::hash::DefaultHasher`]はRustのバージョンによって結果が変わりうるため使わない。
+/// 同じ保存先を別のBotと共有した場合や`discord.client_id`を変更した場合に登録し直すよう、
+/// アプリケーションのIDも含める。
+fn definitions_hash(application_id: ApplicationId, commands: &[CreateCommand]) -> Result<String> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let json = serde_json::to_vec(commands).context("Failed to serialize application commands")?;
+    let hash = application_id
+        .get()
+        .to_le_bytes()
+        .iter()
+        .chain(&json)
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        });
+
+    Ok(format!("{hash:016x}"))
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::super::respond_text;
+use crate::{
+    app_state,
+    db::setting::{GetOption, SetOption},
+};
+
+const SUBCOMMAND_NAME: &str = "catchup";
+const SPEEDUP_OPTION_NAME: &str = "speedup";
+const THRESHOLD_OPTION_NAME: &str = "threshold";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "読み上げ待ちのメッセージが多いときの動作を設定",
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        SPEEDUP_OPTION_NAME,
+        "読み上げ待ちのメッセージの数に応じて読み上げを速くする",
+    ))
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            THRESHOLD_OPTION_NAME,
+            "読み上げ待ちのメッセージがこの数に達したら古いものを省略する（0で無効）",
+        )
+        .min_int_value(0),
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /setting catchup");
+    };
+
+    let state = app_state::get(ctx).await?;
+    let mut setting = state
+        .storage
+        .get_setting(GetOption {
+            guild_id: guild_id.into(),
+        })
+        .await?;
+
+    for suboption in suboptions {
+        match suboption {
+            ResolvedOption {
+                name: SPEEDUP_OPTION_NAME,
+                value: ResolvedValue::Boolean(speedup),
+                ..
+            } => setting.catchup_speedup = *speedup,
+            ResolvedOption {
+                name: THRESHOLD_OPTION_NAME,
+                value: ResolvedValue::Integer(threshold),
+                ..
+            } => {
+                let max_queue_length = state.catchup().max_queue_length;
+                if usize::try_from(*threshold).is_ok_and(|threshold| threshold > max_queue_length) {
+                    respond_text(
+                        ctx,
+                        cmd,
+                        format!("省略する件数には{max_queue_length}以下の数を指定してください。"),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+                setting.catchup_threshold = u32::try_from(*threshold)
+                    .with_context(|| format!("Invalid threshold: {threshold}"))?;
+            }
+            _ => bail!("Failed to parse /setting catchup options"),
+        }
+    }
+
+    if !suboptions.is_empty() {
+        state
+            .storage
+            .set_setting(SetOption {
+                guild_id: guild_id.into(),
+                setting: setting.clone(),
+            })
+            .await?;
+    }
+
+    let speedup = if setting.catchup_speedup {
+        "有効"
+    } else {
+        "無効"
+    };
+    let threshold = if setting.catchup_threshold == 0 {
+        "無効".to_string()
+    } else {
+        format!("{}件以上で古いメッセージを省略", setting.catchup_threshold)
+    };
+    respond_text(
+        ctx,
+        cmd,
+        format!(
+            "読み上げ待ちのメッセージが多いときの設定\n- 読み上げの高速化: {speedup}\n- 省略: \
+             {threshold}"
+        ),
+    )
+    .await?;
+    Ok(())
+}
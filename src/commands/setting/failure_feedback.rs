@@ -0,0 +1,88 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::super::respond_text;
+use crate::{
+    app_state,
+    db::setting::{FailureFeedback, GetOption, SetOption},
+};
+
+const SUBCOMMAND_NAME: &str = "failure_feedback";
+const MODE_OPTION_NAME: &str = "mode";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "メッセージを読み上げられなかったときの知らせ方を設定",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, MODE_OPTION_NAME, "知らせ方")
+            .required(true)
+            .add_string_choice("知らせない", FailureFeedback::Off.as_str())
+            .add_string_choice("リアクションを付ける", FailureFeedback::Reaction.as_str())
+            .add_string_choice("理由を送信する", FailureFeedback::Notice.as_str()),
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /setting failure_feedback");
+    };
+
+    let [
+        ResolvedOption {
+            name: MODE_OPTION_NAME,
+            value: ResolvedValue::String(mode),
+            ..
+        },
+    ] = &suboptions[..]
+    else {
+        bail!("Failed to parse /setting failure_feedback options");
+    };
+    let mode = mode.parse::<FailureFeedback>()?;
+
+    let state = app_state::get(ctx).await?;
+    let mut setting = state
+        .storage
+        .get_setting(GetOption {
+            guild_id: guild_id.into(),
+        })
+        .await?;
+    setting.failure_feedback = mode;
+    state
+        .storage
+        .set_setting(SetOption {
+            guild_id: guild_id.into(),
+            setting,
+        })
+        .await?;
+
+    let msg = match mode {
+        FailureFeedback::Off => "メッセージを読み上げられなかったときに何もしません。",
+        FailureFeedback::Reaction => {
+            "メッセージを読み上げられなかったときに、そのメッセージに⚠️のリアクションを付けます。"
+        }
+        FailureFeedback::Notice => {
+            "メッセージを読み上げられなかったときに、\
+             その理由を読み上げ対象のチャンネルに送信します。"
+        }
+    };
+    respond_text(ctx, cmd, msg).await?;
+    Ok(())
+}
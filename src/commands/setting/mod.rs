@@ -0,0 +1,62 @@
+pub mod catchup;
+pub mod failure_feedback;
+pub mod read_filters;
+pub mod vc_only;
+
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::{
+        application::{CommandInteraction, InteractionContext},
+        permissions::Permissions,
+    },
+};
+
+const COMMAND_NAME: &str = "setting";
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(COMMAND_NAME)
+            .description("サーバーの読み上げ設定")
+            .contexts(vec![InteractionContext::Guild])
+            .default_member_permissions(Permissions::MANAGE_GUILD)
+            .add_option(catchup::subcommand())
+            .add_option(vc_only::subcommand())
+            .add_option(read_filters::subcommand())
+            .add_option(failure_feedback::subcommand()),
+    ]
+}
+
+pub fn matches(cmd: &CommandInteraction) -> bool {
+    cmd.data.name == COMMAND_NAME
+}
+
+pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+    let options = cmd.data.options();
+    let Some(option) = options.first() else {
+        bail!("No subcommand provided for /setting");
+    };
+
+    if catchup::matches(option) {
+        catchup::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /setting catchup")?;
+    } else if vc_only::matches(option) {
+        vc_only::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /setting vc_only")?;
+    } else if read_filters::matches(option) {
+        read_filters::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /setting read_filters")?;
+    } else if failure_feedback::matches(option) {
+        failure_feedback::handle(ctx, cmd, option)
+            .await
+            .context("Failed to execute /setting failure_feedback")?;
+    } else {
+        bail!("Unknown subcommand for /setting: {}", option.name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::super::respond_text;
+use crate::{
+    app_state,
+    db::setting::{GetOption, ReadFilter, SetOption},
+};
+
+const SUBCOMMAND_NAME: &str = "read_filters";
+const FILTERS_OPTION_NAME: &str = "filters";
+const RESET_OPTION_NAME: &str = "reset";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "読み上げる文章を組み立てるときの処理とその順序を設定",
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::String,
+        FILTERS_OPTION_NAME,
+        "適用する処理の名前をカンマ区切りで指定（例: mention,dictionary,url）",
+    ))
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        RESET_OPTION_NAME,
+        "Trueの場合は初期設定に戻す",
+    ))
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /setting read_filters");
+    };
+
+    let mut filters = None;
+    for suboption in suboptions {
+        match suboption {
+            ResolvedOption {
+                name: FILTERS_OPTION_NAME,
+                value: ResolvedValue::String(value),
+                ..
+            } => match parse_filters(value) {
+                Ok(value) => filters = Some(value),
+                Err(msg) => {
+                    respond_text(ctx, cmd, msg).await?;
+                    return Ok(());
+                }
+            },
+            ResolvedOption {
+                name: RESET_OPTION_NAME,
+                value: ResolvedValue::Boolean(reset),
+                ..
+            } => {
+                if *reset {
+                    filters = Some(ReadFilter::DEFAULT_ORDER.to_vec());
+                }
+            }
+            _ => bail!("Failed to parse /setting read_filters options"),
+        }
+    }
+
+    let state = app_state::get(ctx).await?;
+    let mut setting = state
+        .storage
+        .get_setting(GetOption {
+            guild_id: guild_id.into(),
+        })
+        .await?;
+
+    if let Some(filters) = filters {
+        setting.read_filters = filters;
+        state
+            .storage
+            .set_setting(SetOption {
+                guild_id: guild_id.into(),
+                setting: setting.clone(),
+            })
+            .await?;
+    }
+
+    let list = if setting.read_filters.is_empty() {
+        "- なし".to_string()
+    } else {
+        setting
+            .read_filters
+            .iter()
+            .enumerate()
+            .map(|(i, filter)| format!("{}. `{filter}`: {}", i + 1, label(*filter)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    respond_text(
+        ctx,
+        cmd,
+        format!("読み上げる文章を組み立てるときの処理（この順に適用します）\n{list}"),
+    )
+    .await?;
+    Ok(())
+}
+
+/// カンマ区切りの処理の名前を変換する。不正な場合はユーザーに表示するメッセージを返す。
+fn parse_filters(value: &str) -> Result<Vec<ReadFilter>, String> {
+    let Ok(filters) = ReadFilter::split(value) else {
+        let available = ReadFilter::DEFAULT_ORDER
+            .iter()
+            .map(|filter| format!("- `{filter}`: {}", label(*filter)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(format!(
+            "処理の名前が正しくありません。次の名前をカンマ区切りで指定してください。\n{available}"
+        ));
+    };
+
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = filters.iter().find(|filter| !seen.insert(**filter)) {
+        return Err(format!("`{duplicate}`が複数回指定されています。"));
+    }
+
+    Ok(filters)
+}
+
+fn label(filter: ReadFilter) -> &'static str {
+    match filter {
+        ReadFilter::Mention => "メンションを名前に置き換える",
+        ReadFilter::CustomEmoji => "カスタム絵文字を名前に置き換える",
+        ReadFilter::Markdown => "書式とスポイラーを取り除く",
+        ReadFilter::Url => "URLを取り除く",
+        ReadFilter::AuthorName => "送信者の名前を先頭に付ける",
+        ReadFilter::Dictionary => "辞書に登録されている語句を読み替える",
+        ReadFilter::Truncate => "長い文章を省略する",
+    }
+}
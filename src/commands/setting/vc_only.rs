@@ -0,0 +1,83 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::CreateCommandOption,
+    client::Context,
+    model::application::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+};
+
+use super::super::respond_text;
+use crate::{
+    app_state,
+    db::setting::{GetOption, SetOption},
+};
+
+const SUBCOMMAND_NAME: &str = "vc_only";
+const ENABLED_OPTION_NAME: &str = "enabled";
+
+pub fn subcommand() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        SUBCOMMAND_NAME,
+        "Koeと同じボイスチャンネルにいるメンバーのメッセージのみを読み上げる",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            ENABLED_OPTION_NAME,
+            "有効にする場合はTrue",
+        )
+        .required(true),
+    )
+}
+
+pub fn matches(option: &ResolvedOption<'_>) -> bool {
+    option.name == SUBCOMMAND_NAME
+}
+
+pub async fn handle(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    option: &ResolvedOption<'_>,
+) -> Result<()> {
+    let guild_id = cmd
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+    let ResolvedValue::SubCommand(suboptions) = &option.value else {
+        bail!("Invalid subcommand value for /setting vc_only");
+    };
+
+    let [
+        ResolvedOption {
+            name: ENABLED_OPTION_NAME,
+            value: ResolvedValue::Boolean(enabled),
+            ..
+        },
+    ] = &suboptions[..]
+    else {
+        bail!("Failed to parse /setting vc_only options");
+    };
+
+    let state = app_state::get(ctx).await?;
+    let mut setting = state
+        .storage
+        .get_setting(GetOption {
+            guild_id: guild_id.into(),
+        })
+        .await?;
+    setting.vc_members_only = *enabled;
+    state
+        .storage
+        .set_setting(SetOption {
+            guild_id: guild_id.into(),
+            setting,
+        })
+        .await?;
+
+    let msg = if *enabled {
+        "Koeと同じボイスチャンネルにいるメンバーのメッセージのみを読み上げます。"
+    } else {
+        "ボイスチャンネルにいないメンバーのメッセージも読み上げます。"
+    };
+    respond_text(ctx, cmd, msg).await?;
+    Ok(())
+}
@@ -1,27 +1,40 @@
 use anyhow::{Context as _, Result};
 use serenity::{
-    builder::CreateCommand,
+    builder::{CreateCommand, CreateCommandOption},
     client::Context,
-    model::application::{CommandInteraction, InteractionContext},
+    model::application::{
+        CommandInteraction, CommandOptionType, InteractionContext, ResolvedOption, ResolvedValue,
+    },
 };
 
-use super::respond_text;
+use super::{respond_text, respond_text_without_mentions};
 use crate::voice_call;
 
 const COMMAND_NAME: &str = "skip";
 const ALIAS_COMMAND_NAME: &str = "kskip";
+const USER_OPTION_NAME: &str = "user";
 
 pub fn commands() -> Vec<CreateCommand> {
     vec![
         CreateCommand::new(COMMAND_NAME)
             .description("読み上げ中のメッセージをスキップ")
-            .contexts(vec![InteractionContext::Guild]),
+            .contexts(vec![InteractionContext::Guild])
+            .add_option(user_option()),
         CreateCommand::new(ALIAS_COMMAND_NAME)
             .description("読み上げ中のメッセージをスキップ")
-            .contexts(vec![InteractionContext::Guild]),
+            .contexts(vec![InteractionContext::Guild])
+            .add_option(user_option()),
     ]
 }
 
+fn user_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::User,
+        USER_OPTION_NAME,
+        "指定したユーザーのメッセージをすべてスキップ",
+    )
+}
+
 pub fn matches(cmd: &CommandInteraction) -> bool {
     matches!(cmd.data.name.as_str(), COMMAND_NAME | ALIAS_COMMAND_NAME)
 }
@@ -38,6 +51,30 @@ pub async fn handle(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
         };
     }
 
+    let target_user = cmd
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option {
+            ResolvedOption {
+                name: USER_OPTION_NAME,
+                value: ResolvedValue::User(user, _),
+                ..
+            } => Some(user.id),
+            _ => None,
+        });
+
+    if let Some(user_id) = target_user {
+        let count = voice_call::remove_by_author(ctx, guild_id, user_id).await?;
+        respond_text_without_mentions(
+            ctx,
+            cmd,
+            format!("<@{user_id}>のメッセージを{count}件スキップしました。"),
+        )
+        .await?;
+        return Ok(());
+    }
+
     voice_call::skip(ctx, guild_id).await?;
 
     respond_text(ctx, cmd, "読み上げ中のメッセージをスキップしました。").await?;
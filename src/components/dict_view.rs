@@ -0,0 +1,236 @@
+use anyhow::{Context as _, Result, bail};
+use serenity::{
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::{
+        application::ComponentInteraction,
+        id::{GuildId, UserId},
+    },
+};
+
+use super::respond_text_ephemeral;
+use crate::{
+    app_state,
+    db::dict::{DictScope, GetAllOption},
+    util::sanitize_response,
+};
+
+const CUSTOM_ID_PREFIX: &str = "dict_view:";
+
+/// 1ページに表示する項目の数
+const ITEMS_PER_PAGE: usize = 10;
+
+/// 埋め込みに表示する語句と読み方の最大文字数
+const MAX_DISPLAY_CHARS: usize = 100;
+
+/// 検索する文字列の最大文字数
+/// `custom_id`は100文字までなので、他の状態と合わせて収まるように制限する
+pub const MAX_QUERY_CHARS: u16 = 20;
+
+/// 辞書の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// 語句順
+    Word,
+    /// 読み方順
+    ReadAs,
+}
+
+impl SortOrder {
+    fn as_custom_id(self) -> &'static str {
+        match self {
+            Self::Word => "w",
+            Self::ReadAs => "r",
+        }
+    }
+
+    fn from_custom_id(s: &str) -> Option<Self> {
+        match s {
+            "w" => Some(Self::Word),
+            "r" => Some(Self::ReadAs),
+            _ => None,
+        }
+    }
+}
+
+/// 辞書の表示状態
+#[derive(Debug, Clone)]
+pub struct DictView {
+    pub scope: DictScope,
+    pub query: String,
+    pub sort: SortOrder,
+    pub page_idx: usize,
+}
+
+impl DictView {
+    /// 状態を`custom_id`に変換する
+    /// 形式は`dict_view:{page_idx}:{sort}:{user_id}:{query}`で、サーバーの辞書のときは`user_id`を0とする
+    fn to_custom_id(&self, page_idx: usize) -> String {
+        let user_id = match self.scope {
+            DictScope::Guild { .. } => 0,
+            DictScope::User { user_id, .. } => user_id,
+        };
+        format!(
+            "{CUSTOM_ID_PREFIX}{page_idx}:{}:{user_id}:{}",
+            self.sort.as_custom_id(),
+            self.query
+        )
+    }
+
+    fn from_custom_id(guild_id: GuildId, custom_id: &str) -> Option<Self> {
+        let mut parts = custom_id.strip_prefix(CUSTOM_ID_PREFIX)?.splitn(4, ':');
+
+        let page_idx = parts.next()?.parse().ok()?;
+        let sort = SortOrder::from_custom_id(parts.next()?)?;
+        let scope = match parts.next()?.parse().ok()? {
+            0 => DictScope::Guild {
+                guild_id: guild_id.get(),
+            },
+            user_id => DictScope::User {
+                guild_id: guild_id.get(),
+                user_id,
+            },
+        };
+        let query = parts.next()?.to_string();
+
+        Some(Self {
+            scope,
+            query,
+            sort,
+            page_idx,
+        })
+    }
+}
+
+pub fn custom_id_matches(custom_id: &str) -> bool {
+    custom_id.starts_with(CUSTOM_ID_PREFIX)
+}
+
+/// 辞書を表示する埋め込みとページ切り替えボタンを返す
+pub async fn render(
+    ctx: &Context,
+    guild_id: GuildId,
+    view: &DictView,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>)> {
+    let state = app_state::get(ctx).await?;
+    let mut dict = state
+        .storage
+        .get_dict(GetAllOption { scope: view.scope })
+        .await?;
+
+    if !view.query.is_empty() {
+        dict.retain(|(word, read_as)| word.contains(&view.query) || read_as.contains(&view.query));
+    }
+
+    match view.sort {
+        SortOrder::Word => dict.sort(),
+        SortOrder::ReadAs => dict.sort_by(|(w1, r1), (w2, r2)| r1.cmp(r2).then_with(|| w1.cmp(w2))),
+    }
+
+    let page_count = dict.len().div_ceil(ITEMS_PER_PAGE).max(1);
+    let page_idx = view.page_idx.min(page_count - 1);
+
+    let title = match view.scope {
+        DictScope::Guild { .. } => {
+            let guild_name = guild_id
+                .name(&ctx.cache)
+                .unwrap_or_else(|| "サーバー".to_string());
+            format!("📕 {guild_name}の辞書")
+        }
+        DictScope::User { user_id, .. } => {
+            let user_name = UserId::new(user_id).to_user(ctx).await.map_or_else(
+                |_| "メンバー".to_string(),
+                |user| user.display_name().to_string(),
+            );
+            format!("📕 {user_name}専用の辞書")
+        }
+    };
+
+    let mut embed = CreateEmbed::default().title(title);
+
+    if !view.query.is_empty() {
+        embed = embed.description(format!(
+            "「{}」の検索結果",
+            sanitize_response(&truncate(&view.query))
+        ));
+    }
+
+    embed = embed.fields(
+        dict.iter()
+            .skip(page_idx * ITEMS_PER_PAGE)
+            .take(ITEMS_PER_PAGE)
+            .map(|(word, read_as)| (truncate(word), sanitize_response(&truncate(read_as)), false)),
+    );
+
+    embed = embed.footer(CreateEmbedFooter::new(format!(
+        "{}件中 {}/{}ページ",
+        dict.len(),
+        page_idx + 1,
+        page_count
+    )));
+
+    if page_count <= 1 {
+        return Ok((embed, Vec::new()));
+    }
+
+    let buttons = vec![
+        CreateButton::new(view.to_custom_id(page_idx.saturating_sub(1)))
+            .label("◀ 前へ")
+            .disabled(page_idx == 0),
+        CreateButton::new(view.to_custom_id(page_idx + 1))
+            .label("次へ ▶")
+            .disabled(page_idx + 1 >= page_count),
+    ];
+
+    Ok((embed, vec![CreateActionRow::Buttons(buttons)]))
+}
+
+pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .context("Guild ID not available in interaction")?;
+
+    let Some(view) = DictView::from_custom_id(guild_id, &interaction.data.custom_id) else {
+        bail!(
+            "Invalid dictionary view custom_id format: {}",
+            interaction.data.custom_id
+        );
+    };
+
+    // メンバーの辞書は本人のみがページを切り替えられる
+    if let DictScope::User { user_id, .. } = view.scope
+        && interaction.user.id.get() != user_id
+    {
+        respond_text_ephemeral(
+            ctx,
+            interaction,
+            "この辞書は表示したメンバーのみが操作できます。",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (embed, components) = render(ctx, guild_id, &view).await?;
+
+    let message = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components);
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() > MAX_DISPLAY_CHARS {
+        text.chars().take(MAX_DISPLAY_CHARS - 1).collect::<String>() + "…"
+    } else {
+        text.to_string()
+    }
+}
@@ -1,3 +1,4 @@
+pub mod dict_view;
 pub mod voice_select;
 
 use anyhow::{Context as _, Result, bail};
@@ -7,11 +8,17 @@ use serenity::{
     model::application::ComponentInteraction,
 };
 
+use crate::commands::STORAGE_UNAVAILABLE_MESSAGE;
+
 pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
     if voice_select::custom_id_matches(&interaction.data.custom_id) {
         voice_select::handle_interaction(ctx, interaction)
             .await
             .context(r#"Failed to handle "voice" message component interaction"#)?;
+    } else if dict_view::custom_id_matches(&interaction.data.custom_id) {
+        dict_view::handle_interaction(ctx, interaction)
+            .await
+            .context(r#"Failed to handle "dict_view" message component interaction"#)?;
     } else {
         bail!(
             "Unknown message component interaction custom_id: {}",
@@ -22,6 +29,24 @@ pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteractio
     Ok(())
 }
 
+/// Notifies the user who interacted with the component that it failed because the storage is
+/// unavailable
+pub async fn respond_storage_unavailable(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+) -> Result<()> {
+    let message = CreateInteractionResponseMessage::new()
+        .content(STORAGE_UNAVAILABLE_MESSAGE)
+        .ephemeral(true);
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
+
 /// Helper function to create text message response
 async fn respond_text(
     ctx: &Context,
@@ -37,3 +62,22 @@ async fn respond_text(
 
     Ok(())
 }
+
+/// Helper function to create text message response visible only to the user who interacted with
+/// the component
+async fn respond_text_ephemeral(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    text: impl Into<String>,
+) -> Result<()> {
+    let message = CreateInteractionResponseMessage::new()
+        .content(text)
+        .ephemeral(true);
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .context("Failed to create interaction response")?;
+
+    Ok(())
+}
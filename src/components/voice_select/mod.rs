@@ -2,7 +2,6 @@ mod button;
 mod select;
 
 use anyhow::{Context as _, Result, bail};
-use log::warn;
 use rand::seq::IndexedRandom as _;
 use serenity::{
     builder::CreateActionRow,
@@ -12,11 +11,9 @@ use serenity::{
         id::{GuildId, UserId},
     },
 };
+use tracing::warn;
 
-use crate::{
-    app_state,
-    db::{self, voice::GetOption},
-};
+use crate::{app_state, db::voice::GetOption};
 
 pub fn custom_id_matches(custom_id: &str) -> bool {
     select::custom_id_matches(custom_id) || button::custom_id_matches(custom_id)
@@ -30,28 +27,22 @@ pub async fn components(
 ) -> Result<Vec<CreateActionRow>> {
     let state = app_state::get(ctx).await?;
 
-    let available_presets = state.voicevox_client.presets().await?;
+    let available_presets = state.voicevox_client().presets().await?;
 
     let current_preset = {
-        let mut conn = state
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-
         let fallback_preset_id = available_presets
             .choose(&mut rand::rng())
             .map(|p| p.id)
             .context("No presets available")?;
 
-        db::voice::get(
-            &mut conn,
-            GetOption {
+        state
+            .storage
+            .get_voice(GetOption {
                 guild_id: guild_id.into(),
                 user_id: user_id.into(),
                 fallback: fallback_preset_id,
-            },
-        )
-        .await?
+            })
+            .await?
     };
 
     const MAX_ITEMS_PER_PAGE: usize = 25;
@@ -9,10 +9,7 @@ use serenity::{
 };
 
 use super::super::respond_text;
-use crate::{
-    app_state,
-    db::{self, voice::SetOption},
-};
+use crate::{app_state, db::voice::SetOption};
 
 const CUSTOM_ID_VOICE_SELECT: &str = "voice";
 
@@ -56,7 +53,7 @@ pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteractio
 
     let state = app_state::get(ctx).await?;
 
-    let available_presets = state.voicevox_client.presets().await?;
+    let available_presets = state.voicevox_client().presets().await?;
     let Some(selected_preset) = available_presets
         .into_iter()
         .find(|p| p.id == selected_preset_id)
@@ -74,19 +71,14 @@ pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteractio
     };
 
     {
-        let mut conn = state
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-        db::voice::set(
-            &mut conn,
-            SetOption {
+        state
+            .storage
+            .set_voice(SetOption {
                 guild_id: guild_id.into(),
                 user_id: interaction.user.id.into(),
                 value: selected_preset_id,
-            },
-        )
-        .await?;
+            })
+            .await?;
     }
 
     respond_text(
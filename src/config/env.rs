@@ -0,0 +1,189 @@
+//! 環境変数とファイルによる設定の上書き
+//!
+//! - `KOE_DISCORD__BOT_TOKEN`のように、`KOE_`に続けて設定のキーを`__`で区切って指定すると、
+//!   設定ファイルの`discord.bot_token`を上書きする。
+//! - キーの末尾に`_file`を付けると、値をファイルのパスとみなし、ファイルの内容を設定の値とする。
+//!   例えば`discord.bot_token_file: /run/secrets/bot_token`や
+//!   `KOE_DISCORD__BOT_TOKEN_FILE=/run/secrets/bot_token`のように指定する。
+//! - 環境変数やファイルの値は文字列として設定に入れる。数値や真偽値の項目は
+//!   [`from_str_or_value`]で文字列からも読み込めるようにしている。
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{Context as _, Result, bail};
+use serde::{Deserialize, Deserializer, de};
+use serde_yaml::{Mapping, Value};
+
+const PREFIX: &str = "KOE_";
+const SEPARATOR: &str = "__";
+const FILE_SUFFIX: &str = "_file";
+
+/// 環境変数で`config`を上書きする
+///
+/// `KOE_`で始まり`__`を含む環境変数のみを設定のキーとみなす。
+/// `KOE_CONFIG`などのそれ以外の環境変数は無視する。
+pub fn apply_overrides(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<()> {
+    for (name, value) in vars {
+        let Some(key) = name.strip_prefix(PREFIX) else {
+            continue;
+        };
+        if !key.contains(SEPARATOR) {
+            continue;
+        }
+
+        let mut path = key.split(SEPARATOR).map(str::to_string).collect::<Vec<_>>();
+        // 辞書の語句は設定のキーではないため、大文字と小文字を区別する
+        let case_sensitive_from = if path.len() > 2
+            && path[0].eq_ignore_ascii_case("dict")
+            && path[1].eq_ignore_ascii_case("global")
+        {
+            2
+        } else {
+            path.len()
+        };
+        for segment in &mut path[..case_sensitive_from] {
+            *segment = segment.to_lowercase();
+        }
+        if path.iter().any(String::is_empty) {
+            bail!("Invalid config environment variable {name}: empty key");
+        }
+
+        set(config, &path, Value::String(value))
+            .with_context(|| format!("Failed to apply environment variable {name}"))?;
+    }
+
+    Ok(())
+}
+
+/// `path`が指す値を`value`に置き換える
+/// 値とそのファイル版（`_file`の付いたキー）のうち、置き換えなかった方は削除する
+fn set(config: &mut Value, path: &[String], value: Value) -> Result<()> {
+    let (last, parents) = path.split_last().context("Empty config key")?;
+
+    let mut mapping = as_mapping(config, "")?;
+    for (i, key) in parents.iter().enumerate() {
+        let child = mapping
+            .entry(Value::String(key.clone()))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        mapping = as_mapping(child, &path[..=i].join("."))?;
+    }
+
+    let counterpart = match last.strip_suffix(FILE_SUFFIX) {
+        Some(key) => key.to_string(),
+        None => format!("{last}{FILE_SUFFIX}"),
+    };
+    mapping.remove(counterpart.as_str());
+    mapping.insert(Value::String(last.clone()), value);
+
+    Ok(())
+}
+
+fn as_mapping<'a>(value: &'a mut Value, key: &str) -> Result<&'a mut Mapping> {
+    if value.is_null() {
+        *value = Value::Mapping(Mapping::new());
+    }
+
+    match value {
+        Value::Mapping(mapping) => Ok(mapping),
+        _ => bail!("Config `{key}` is not a mapping"),
+    }
+}
+
+/// `_file`で終わるキーの値をファイルのパスとみなし、ファイルの内容で置き換える
+pub fn resolve_files(config: &mut Value) -> Result<()> {
+    resolve_files_in(config, "")
+}
+
+fn resolve_files_in(value: &mut Value, path: &str) -> Result<()> {
+    let Value::Mapping(mapping) = value else {
+        return Ok(());
+    };
+
+    let keys = mapping
+        .keys()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    for key in keys {
+        let key_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+
+        // 辞書の語句は設定のキーではない
+        if key_path == "dict.global" {
+            continue;
+        }
+
+        let Some(target) = key.strip_suffix(FILE_SUFFIX) else {
+            resolve_files_in(&mut mapping[key.as_str()], &key_path)?;
+            continue;
+        };
+
+        let target_path = key_path
+            .strip_suffix(FILE_SUFFIX)
+            .unwrap_or(&key_path)
+            .to_string();
+        if mapping.get(target).is_some_and(|value| !value.is_null()) {
+            bail!("Invalid config `{key_path}`: `{target_path}` is also set");
+        }
+
+        let Some(file_path) = mapping.remove(key.as_str()) else {
+            continue;
+        };
+        let Value::String(file_path) = file_path else {
+            bail!("Invalid config `{key_path}`: expected a file path");
+        };
+
+        let content = std::fs::read_to_string(&file_path)
+            .with_context(|| format!("Invalid config `{key_path}`: failed to read {file_path}"))?;
+        // ファイルの末尾の改行は値に含めない
+        let content = content.trim_end_matches(['\r', '\n']);
+
+        mapping.insert(
+            Value::String(target.to_string()),
+            Value::String(content.to_string()),
+        );
+    }
+
+    Ok(())
+}
+
+/// 数値や真偽値の項目を、設定ファイルに書かれた値と環境変数やファイルから読み込んだ文字列の両方から読み込む
+pub fn from_str_or_value<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match StrOrValue::<T>::deserialize(deserializer)? {
+        StrOrValue::Str(value) => value.parse().map_err(de::Error::custom),
+        StrOrValue::Value(value) => Ok(value),
+    }
+}
+
+/// 省略できる項目に使う[`from_str_or_value`]
+pub fn option_from_str_or_value<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match Option::<StrOrValue<T>>::deserialize(deserializer)? {
+        Some(StrOrValue::Str(value)) => value.parse().map(Some).map_err(de::Error::custom),
+        Some(StrOrValue::Value(value)) => Ok(Some(value)),
+        None => Ok(None),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrOrValue<T> {
+    Str(String),
+    Value(T),
+}
@@ -0,0 +1,475 @@
+mod env;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt, io::ErrorKind, net::SocketAddr, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use redis::IntoConnectionInfo as _;
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Config {
+    pub discord: DiscordConfig,
+    pub voicevox: VoicevoxConfig,
+    #[serde(default)]
+    pub redis: Option<RedisConfig>,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub dict: DictConfig,
+    /// 監視用のHTTPサーバーの設定。省略した場合は起動しない
+    #[serde(default)]
+    pub http: Option<HttpConfig>,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub catchup: CatchupConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DiscordConfig {
+    #[serde(deserialize_with = "env::from_str_or_value")]
+    pub client_id: u64,
+    pub bot_token: String,
+    /// スラッシュコマンドを登録する範囲
+    #[serde(default)]
+    pub command_scope: CommandScope,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandScope {
+    /// すべてのサーバーで使えるように一度だけ登録し、定義が変わったときのみ登録し直す
+    Global,
+    /// サーバーに参加するたびにそのサーバーに登録する
+    ///
+    /// 以前のバージョンと同じ動作で、既存の環境でコマンドが重複しないようデフォルトとする
+    #[default]
+    Guild,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VoicevoxConfig {
+    pub api_base: String,
+    /// 話者の初期化が終わるまでメッセージの読み上げを待つか
+    #[serde(default, deserialize_with = "env::from_str_or_value")]
+    pub wait_for_warmup: bool,
+    /// VOICEVOX ENGINEへの接続のタイムアウト（ミリ秒）
+    #[serde(
+        default = "default_voicevox_connect_timeout_ms",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub connect_timeout_ms: u64,
+    /// リクエストを送ってから応答を受け取り終えるまでのタイムアウト（ミリ秒）
+    #[serde(
+        default = "default_voicevox_request_timeout_ms",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub request_timeout_ms: u64,
+    /// 一時的なエラーで失敗したリクエストを再試行する回数
+    #[serde(
+        default = "default_voicevox_max_retries",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub max_retries: u32,
+}
+
+const fn default_voicevox_connect_timeout_ms() -> u64 {
+    3000
+}
+
+const fn default_voicevox_request_timeout_ms() -> u64 {
+    30000
+}
+
+const fn default_voicevox_max_retries() -> u32 {
+    2
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RedisConfig {
+    pub url: String,
+    /// Redisへの接続のタイムアウト（ミリ秒）
+    #[serde(
+        default = "default_redis_connection_timeout_ms",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub connection_timeout_ms: u64,
+    /// コマンドの応答のタイムアウト（ミリ秒）
+    #[serde(
+        default = "default_redis_response_timeout_ms",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub response_timeout_ms: u64,
+    /// 接続が切れたときに再接続を試みる回数
+    #[serde(
+        default = "default_redis_max_retries",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub max_retries: usize,
+}
+
+const fn default_redis_connection_timeout_ms() -> u64 {
+    5000
+}
+
+const fn default_redis_response_timeout_ms() -> u64 {
+    3000
+}
+
+const fn default_redis_max_retries() -> usize {
+    5
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct StorageConfig {
+    /// 設定や辞書の保存先
+    #[serde(default)]
+    pub backend: StorageBackend,
+    pub sqlite: Option<SqliteConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    #[default]
+    Redis,
+    Sqlite,
+    /// Koeを終了するとすべてのデータが失われる
+    Memory,
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Redis => "redis",
+            Self::Sqlite => "sqlite",
+            Self::Memory => "memory",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SqliteConfig {
+    /// データベースのファイルのパス
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HttpConfig {
+    /// 待ち受けるアドレスとポート（例: `0.0.0.0:9090`）
+    pub listen: SocketAddr,
+}
+
+/// SIGTERMまたはSIGINTを受け取ったときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ShutdownConfig {
+    /// 読み上げ中のメッセージを待つ最大の時間（秒）
+    /// 経過するとボイスチャンネルから切断して終了する
+    #[serde(
+        default = "default_shutdown_timeout_secs",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub timeout_secs: u64,
+    /// 読み上げ中や読み上げ待ちのメッセージの扱い
+    #[serde(default)]
+    pub in_flight: InFlightPolicy,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_shutdown_timeout_secs(),
+            in_flight: InFlightPolicy::default(),
+        }
+    }
+}
+
+/// `docker stop`が強制終了するまでの10秒に収まるようにする
+const fn default_shutdown_timeout_secs() -> u64 {
+    8
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InFlightPolicy {
+    /// すべて読み上げてから終了する
+    #[default]
+    Finish,
+    /// 読み上げを中断し、終了することを読み上げる
+    Announce,
+    /// 読み上げを中断する
+    Discard,
+}
+
+/// 読み上げ待ちのメッセージが多いときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CatchupConfig {
+    /// 読み上げ中のメッセージを含めた読み上げ待ちのメッセージの数の上限
+    /// 達すると新しいメッセージは読み上げない
+    #[serde(
+        default = "default_catchup_max_queue_length",
+        deserialize_with = "env::from_str_or_value"
+    )]
+    pub max_queue_length: usize,
+    /// 省略したメッセージの件数を読み上げるプリセット
+    /// 省略した場合はVOICEVOX ENGINEの最初のプリセットを使う
+    #[serde(default, deserialize_with = "env::option_from_str_or_value")]
+    pub summary_preset_id: Option<i64>,
+}
+
+impl Default for CatchupConfig {
+    fn default() -> Self {
+        Self {
+            max_queue_length: default_catchup_max_queue_length(),
+            summary_preset_id: None,
+        }
+    }
+}
+
+const fn default_catchup_max_queue_length() -> usize {
+    50
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct DictConfig {
+    /// すべてのサーバーに適用される辞書
+    #[serde(default)]
+    pub global: HashMap<String, String>,
+}
+
+/// 設定の項目の変更
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    /// 変更された項目のキー
+    pub key: &'static str,
+    /// Koeを再起動せずに反映できるか
+    pub reloadable: bool,
+}
+
+impl Config {
+    /// `self`から`new`への変更を返す
+    pub fn changes(&self, new: &Self) -> Vec<Change> {
+        fn redis_changed<T: PartialEq>(
+            old: &Config,
+            new: &Config,
+            f: fn(&RedisConfig) -> T,
+        ) -> bool {
+            old.redis.as_ref().map(f) != new.redis.as_ref().map(f)
+        }
+
+        let old = self;
+        macro_rules! changed {
+            ($($field:ident).+) => {
+                old.$($field).+ != new.$($field).+
+            };
+        }
+
+        let mut changes = Vec::new();
+        let mut check = |key, reloadable, changed| {
+            if changed {
+                changes.push(Change { key, reloadable });
+            }
+        };
+
+        check("discord.client_id", false, changed!(discord.client_id));
+        check("discord.bot_token", false, changed!(discord.bot_token));
+        check(
+            "discord.command_scope",
+            false,
+            changed!(discord.command_scope),
+        );
+        check("voicevox.api_base", true, changed!(voicevox.api_base));
+        check(
+            "voicevox.wait_for_warmup",
+            true,
+            changed!(voicevox.wait_for_warmup),
+        );
+        check(
+            "voicevox.connect_timeout_ms",
+            true,
+            changed!(voicevox.connect_timeout_ms),
+        );
+        check(
+            "voicevox.request_timeout_ms",
+            true,
+            changed!(voicevox.request_timeout_ms),
+        );
+        check("voicevox.max_retries", true, changed!(voicevox.max_retries));
+        check(
+            "redis.url",
+            false,
+            redis_changed(old, new, |redis| redis.url.clone()),
+        );
+        check(
+            "redis.connection_timeout_ms",
+            false,
+            redis_changed(old, new, |redis| redis.connection_timeout_ms),
+        );
+        check(
+            "redis.response_timeout_ms",
+            false,
+            redis_changed(old, new, |redis| redis.response_timeout_ms),
+        );
+        check(
+            "redis.max_retries",
+            false,
+            redis_changed(old, new, |redis| redis.max_retries),
+        );
+        check("storage.backend", false, changed!(storage.backend));
+        check("storage.sqlite.path", false, changed!(storage.sqlite));
+        check("dict.global", true, changed!(dict.global));
+        check(
+            "catchup.max_queue_length",
+            true,
+            changed!(catchup.max_queue_length),
+        );
+        check(
+            "catchup.summary_preset_id",
+            true,
+            changed!(catchup.summary_preset_id),
+        );
+        check("http.listen", false, changed!(http));
+        check(
+            "shutdown.timeout_secs",
+            false,
+            changed!(shutdown.timeout_secs),
+        );
+        check("shutdown.in_flight", false, changed!(shutdown.in_flight));
+
+        changes
+    }
+
+    /// `new`のうち、Koeを再起動せずに反映できる項目を`self`に反映する
+    pub fn apply_reloadable(&mut self, new: &Self) {
+        self.voicevox = new.voicevox.clone();
+        self.dict = new.dict.clone();
+        self.catchup = new.catchup;
+    }
+}
+
+const DEFAULT_CONFIG_PATH: &str = "/etc/koe.yaml";
+
+/// 読み込む設定ファイルのパスを返す
+/// `KOE_CONFIG`が設定されておらず、`/etc/koe.yaml`も存在しない場合は`None`を返す
+pub fn file_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("KOE_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    let path = PathBuf::from(DEFAULT_CONFIG_PATH);
+    path.exists().then_some(path)
+}
+
+/// 設定ファイルを読み込み、環境変数とファイルによる上書きを適用する
+///
+/// `KOE_CONFIG`が設定されていない場合、設定ファイルが存在しなければ環境変数のみから設定を読み込む。
+pub async fn load() -> Result<Config> {
+    let config_path = std::env::var("KOE_CONFIG").ok();
+
+    let yaml = match &config_path {
+        Some(path) => Some(
+            tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to load config file from {path}"))?,
+        ),
+        None => match tokio::fs::read_to_string(DEFAULT_CONFIG_PATH).await {
+            Ok(yaml) => Some(yaml),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("Failed to load config file from {DEFAULT_CONFIG_PATH}")
+                });
+            }
+        },
+    };
+
+    let mut value = match yaml {
+        Some(yaml) => serde_yaml::from_str(&yaml).context("Failed to parse config file")?,
+        None => Value::Null,
+    };
+
+    env::apply_overrides(&mut value, std::env::vars())?;
+    env::resolve_files(&mut value)?;
+
+    parse(value)
+}
+
+/// 設定を[`Config`]に変換し、値を検証する
+fn parse(value: Value) -> Result<Config> {
+    let value = if value.is_null() {
+        Value::Mapping(Mapping::new())
+    } else {
+        value
+    };
+
+    let config: Config = serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        if path == "." {
+            anyhow!("Invalid config: {}", err.inner())
+        } else {
+            anyhow!("Invalid config `{path}`: {}", err.inner())
+        }
+    })?;
+
+    config.validate()?;
+
+    Ok(config)
+}
+
+impl Config {
+    /// 設定の値を検証し、不正な値があればそのキーをすべて含むエラーを返す
+    fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self.discord.bot_token.trim().is_empty() {
+            errors.push("`discord.bot_token`: must not be empty".to_string());
+        }
+
+        match reqwest::Url::parse(&self.voicevox.api_base) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(_) => errors.push("`voicevox.api_base`: must be an http(s) URL".to_string()),
+            Err(err) => errors.push(format!("`voicevox.api_base`: {err}")),
+        }
+        if self.voicevox.connect_timeout_ms == 0 {
+            errors.push("`voicevox.connect_timeout_ms`: must be greater than 0".to_string());
+        }
+        if self.voicevox.request_timeout_ms == 0 {
+            errors.push("`voicevox.request_timeout_ms`: must be greater than 0".to_string());
+        }
+
+        if let Some(redis) = &self.redis {
+            if let Err(err) = redis.url.as_str().into_connection_info() {
+                errors.push(format!("`redis.url`: {err}"));
+            }
+            if redis.connection_timeout_ms == 0 {
+                errors.push("`redis.connection_timeout_ms`: must be greater than 0".to_string());
+            }
+            if redis.response_timeout_ms == 0 {
+                errors.push("`redis.response_timeout_ms`: must be greater than 0".to_string());
+            }
+        }
+
+        if self.catchup.max_queue_length == 0 {
+            errors.push("`catchup.max_queue_length`: must be greater than 0".to_string());
+        }
+
+        match self.storage.backend {
+            StorageBackend::Redis if self.redis.is_none() => {
+                errors.push("`redis`: must be set when `storage.backend` is redis".to_string());
+            }
+            StorageBackend::Sqlite if self.storage.sqlite.is_none() => errors
+                .push("`storage.sqlite`: must be set when `storage.backend` is sqlite".to_string()),
+            _ => {}
+        }
+
+        if !errors.is_empty() {
+            bail!("Invalid config:\n- {}", errors.join("\n- "));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use std::path::PathBuf;
+
+use serde_yaml::Value;
+
+use super::{StorageBackend, env, parse};
+
+const YAML: &str = r"
+discord:
+  client_id: 1
+  bot_token: TOKEN_IN_FILE
+voicevox:
+  api_base: http://voicevox:50021
+redis:
+  url: redis://redis
+";
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+        .collect()
+}
+
+/// 一時ファイルに`content`を書き込み、そのパスを返す
+fn write_temp_file(content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("koe-config-test-{}", rand::random::<u64>()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn environment_variables_override_file() {
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+
+    env::apply_overrides(
+        &mut value,
+        vars(&[
+            ("KOE_DISCORD__BOT_TOKEN", "TOKEN_IN_ENV"),
+            ("KOE_DISCORD__CLIENT_ID", "42"),
+            ("KOE_REDIS__MAX_RETRIES", "0"),
+            ("KOE_STORAGE__BACKEND", "memory"),
+            ("KOE_CONFIG", "/etc/koe.yaml"),
+            ("KOE_TEST_REDIS_URL", "redis://localhost"),
+            ("HOME", "/root"),
+        ]),
+    )
+    .unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.discord.bot_token, "TOKEN_IN_ENV");
+    assert_eq!(config.discord.client_id, 42);
+    assert_eq!(config.redis.unwrap().max_retries, 0);
+    assert_eq!(config.storage.backend, StorageBackend::Memory);
+}
+
+#[test]
+fn config_can_be_loaded_only_from_environment_variables() {
+    let mut value = Value::Null;
+
+    env::apply_overrides(
+        &mut value,
+        vars(&[
+            ("KOE_DISCORD__CLIENT_ID", "1"),
+            ("KOE_DISCORD__BOT_TOKEN", "TOKEN"),
+            ("KOE_VOICEVOX__API_BASE", "http://localhost:50021"),
+            ("KOE_STORAGE__BACKEND", "sqlite"),
+            ("KOE_STORAGE__SQLITE__PATH", "/data/koe.sqlite3"),
+        ]),
+    )
+    .unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.storage.backend, StorageBackend::Sqlite);
+    assert_eq!(
+        config.storage.sqlite.unwrap().path,
+        PathBuf::from("/data/koe.sqlite3")
+    );
+}
+
+#[test]
+fn secret_is_read_from_file_given_by_environment_variable() {
+    let path = write_temp_file("TOKEN_IN_SECRET\n");
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+
+    env::apply_overrides(
+        &mut value,
+        vars(&[("KOE_DISCORD__BOT_TOKEN_FILE", path.to_str().unwrap())]),
+    )
+    .unwrap();
+    env::resolve_files(&mut value).unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.discord.bot_token, "TOKEN_IN_SECRET");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn secret_is_read_from_file_given_by_config_file() {
+    let path = write_temp_file("redis://:password@redis\n");
+    let yaml = YAML.replace(
+        "  url: redis://redis",
+        &format!("  url_file: {}", path.display()),
+    );
+    let mut value: Value = serde_yaml::from_str(&yaml).unwrap();
+
+    env::resolve_files(&mut value).unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.redis.unwrap().url, "redis://:password@redis");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn secret_that_looks_like_number_is_read_as_string() {
+    let path = write_temp_file("12345\n");
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+
+    env::apply_overrides(
+        &mut value,
+        vars(&[("KOE_DISCORD__BOT_TOKEN_FILE", path.to_str().unwrap())]),
+    )
+    .unwrap();
+    env::resolve_files(&mut value).unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.discord.bot_token, "12345");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn numeric_looking_environment_variables_are_read_as_strings() {
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+
+    env::apply_overrides(
+        &mut value,
+        vars(&[
+            ("KOE_DISCORD__BOT_TOKEN", "12345"),
+            ("KOE_DICT__GLOBAL__ok", "100"),
+            ("KOE_VOICEVOX__WAIT_FOR_WARMUP", "true"),
+            ("KOE_CATCHUP__SUMMARY_PRESET_ID", "3"),
+        ]),
+    )
+    .unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.discord.bot_token, "12345");
+    assert_eq!(
+        config.dict.global.get("ok").map(String::as_str),
+        Some("100")
+    );
+    assert!(config.voicevox.wait_for_warmup);
+    assert_eq!(config.catchup.summary_preset_id, Some(3));
+}
+
+#[test]
+fn numeric_setting_is_read_from_file() {
+    let path = write_temp_file("42\n");
+    let yaml = YAML.replace(
+        "  client_id: 1",
+        &format!("  client_id_file: {}", path.display()),
+    );
+    let mut value: Value = serde_yaml::from_str(&yaml).unwrap();
+
+    env::resolve_files(&mut value).unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(config.discord.client_id, 42);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn environment_variables_keep_case_of_dictionary_words() {
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+
+    env::apply_overrides(&mut value, vars(&[("KOE_DICT__GLOBAL__Koe", "こえ")])).unwrap();
+    let config = parse(value).unwrap();
+
+    assert_eq!(
+        config.dict.global.get("Koe").map(String::as_str),
+        Some("こえ")
+    );
+    assert!(!config.dict.global.contains_key("koe"));
+}
+
+#[test]
+fn value_and_file_cannot_both_be_set() {
+    let yaml = YAML.replace(
+        "  bot_token: TOKEN_IN_FILE",
+        "  bot_token: TOKEN_IN_FILE\n  bot_token_file: /run/secrets/bot_token",
+    );
+    let mut value: Value = serde_yaml::from_str(&yaml).unwrap();
+
+    let err = env::resolve_files(&mut value).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Invalid config `discord.bot_token_file`: `discord.bot_token` is also set"
+    );
+}
+
+#[test]
+fn missing_secret_file_names_key() {
+    let yaml = YAML.replace(
+        "  bot_token: TOKEN_IN_FILE",
+        "  bot_token_file: /nonexistent/bot_token",
+    );
+    let mut value: Value = serde_yaml::from_str(&yaml).unwrap();
+
+    let err = env::resolve_files(&mut value).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Invalid config `discord.bot_token_file`: failed to read /nonexistent/bot_token"
+    );
+}
+
+#[test]
+fn invalid_type_names_key() {
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+    env::apply_overrides(&mut value, vars(&[("KOE_DISCORD__CLIENT_ID", "abc")])).unwrap();
+
+    let err = parse(value).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Invalid config `discord.client_id`: invalid digit found in string"
+    );
+}
+
+#[test]
+fn missing_field_names_key() {
+    let yaml = YAML.replace("  bot_token: TOKEN_IN_FILE\n", "");
+    let value: Value = serde_yaml::from_str(&yaml).unwrap();
+
+    let err = parse(value).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Invalid config `discord`: missing field `bot_token`"
+    );
+}
+
+#[test]
+fn validation_errors_name_every_key() {
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+    env::apply_overrides(
+        &mut value,
+        vars(&[
+            ("KOE_DISCORD__BOT_TOKEN", ""),
+            ("KOE_VOICEVOX__API_BASE", "voicevox:50021"),
+            ("KOE_REDIS__RESPONSE_TIMEOUT_MS", "0"),
+            ("KOE_CATCHUP__MAX_QUEUE_LENGTH", "0"),
+        ]),
+    )
+    .unwrap();
+
+    let err = parse(value).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Invalid config:\n- `discord.bot_token`: must not be empty\n- `voicevox.api_base`: must \
+         be an http(s) URL\n- `redis.response_timeout_ms`: must be greater than 0\n- \
+         `catchup.max_queue_length`: must be greater than 0"
+    );
+}
+
+#[test]
+fn changes_distinguish_reloadable_settings() {
+    let old = parse(serde_yaml::from_str(YAML).unwrap()).unwrap();
+    let mut value: Value = serde_yaml::from_str(YAML).unwrap();
+    env::apply_overrides(
+        &mut value,
+        vars(&[
+            ("KOE_VOICEVOX__API_BASE", "http://localhost:50021"),
+            ("KOE_REDIS__MAX_RETRIES", "1"),
+            ("KOE_DICT__GLOBAL__KOE", "こえ"),
+        ]),
+    )
+    .unwrap();
+    let new = parse(value).unwrap();
+
+    let changes = old
+        .changes(&new)
+        .into_iter()
+        .map(|change| (change.key, change.reloadable))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![
+            ("voicevox.api_base", true),
+            ("redis.max_retries", false),
+            ("dict.global", true),
+        ]
+    );
+
+    let mut applied = old.clone();
+    applied.apply_reloadable(&new);
+    assert_eq!(applied.voicevox, new.voicevox);
+    assert_eq!(applied.dict, new.dict);
+    assert_eq!(applied.redis, old.redis);
+    assert!(old.changes(&old).is_empty());
+}
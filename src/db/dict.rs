@@ -1,9 +1,15 @@
-use anyhow::{Result, bail};
-use redis::{AsyncCommands, aio::MultiplexedConnection};
+/// 辞書の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DictScope {
+    /// サーバーの辞書
+    Guild { guild_id: u64 },
+    /// サーバー内のユーザーの辞書。そのユーザーのメッセージにのみ適用される
+    User { guild_id: u64, user_id: u64 },
+}
 
 #[derive(Debug, Clone)]
 pub struct InsertOption {
-    pub guild_id: u64,
+    pub scope: DictScope,
     pub word: String,
     pub read_as: String,
 }
@@ -14,25 +20,22 @@ pub enum InsertResponse {
     WordAlreadyExists,
 }
 
-/// 辞書に語句を追加する
-pub async fn insert(
-    connection: &mut MultiplexedConnection,
-    option: InsertOption,
-) -> Result<InsertResponse> {
-    let resp = connection
-        .hset_nx(dict_key(option.guild_id), option.word, option.read_as)
-        .await?;
+#[derive(Debug, Clone)]
+pub struct UpdateOption {
+    pub scope: DictScope,
+    pub word: String,
+    pub read_as: String,
+}
 
-    Ok(match resp {
-        0 => InsertResponse::WordAlreadyExists,
-        1 => InsertResponse::Success,
-        x => bail!("Unknown HSETNX response from Redis: {x}"),
-    })
+#[derive(Debug, Clone)]
+pub enum UpdateResponse {
+    Success,
+    WordDoesNotExist,
 }
 
 #[derive(Debug, Clone)]
 pub struct RemoveOption {
-    pub guild_id: u64,
+    pub scope: DictScope,
     pub word: String,
 }
 
@@ -42,37 +45,7 @@ pub enum RemoveResponse {
     WordDoesNotExist,
 }
 
-/// 辞書から語句を削除する
-pub async fn remove(
-    connection: &mut MultiplexedConnection,
-    option: RemoveOption,
-) -> Result<RemoveResponse> {
-    let resp = connection
-        .hdel(dict_key(option.guild_id), option.word)
-        .await?;
-
-    Ok(match resp {
-        0 => RemoveResponse::WordDoesNotExist,
-        1 => RemoveResponse::Success,
-        x => bail!("Unknown HDEL response from Redis: {x}"),
-    })
-}
-
 #[derive(Debug, Clone)]
 pub struct GetAllOption {
-    pub guild_id: u64,
-}
-
-/// 辞書全体を返す
-/// 辞書が存在しないときは空の[`Vec`]を返す
-pub async fn get_all(
-    connection: &mut MultiplexedConnection,
-    option: GetAllOption,
-) -> Result<Vec<(String, String)>> {
-    let resp = connection.hgetall(dict_key(option.guild_id)).await?;
-    Ok(resp)
-}
-
-fn dict_key(guild_id: u64) -> String {
-    format!("guild:{guild_id}:dict")
+    pub scope: DictScope,
 }
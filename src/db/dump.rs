@@ -0,0 +1,147 @@
+//! 保存先の間でデータを移行するための、保存されているすべてのデータ
+
+use anyhow::{Result, bail};
+
+use super::{
+    Storage,
+    dict::{self, DictScope},
+    ignore::{self, IgnoreTarget},
+    name,
+    setting::{self, GuildSetting},
+    user, voice,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// 保存されているすべてのデータ
+#[derive(Debug, Clone, Default)]
+pub struct Dump {
+    pub dicts: Vec<DictDump>,
+    pub voices: Vec<VoiceDump>,
+    pub settings: Vec<SettingDump>,
+    pub ignore_targets: Vec<IgnoreTargetDump>,
+    pub names: Vec<NameDump>,
+    /// 読み上げを拒否しているユーザー
+    pub opted_out_users: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DictDump {
+    pub scope: DictScope,
+    pub entries: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_field_names)]
+pub struct VoiceDump {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub preset_id: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SettingDump {
+    pub guild_id: u64,
+    pub setting: GuildSetting,
+}
+
+#[derive(Debug, Clone)]
+pub struct IgnoreTargetDump {
+    pub guild_id: u64,
+    pub target: IgnoreTarget,
+}
+
+#[derive(Debug, Clone)]
+pub struct NameDump {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub reading: String,
+}
+
+impl Dump {
+    pub fn is_empty(&self) -> bool {
+        self.dicts.is_empty()
+            && self.voices.is_empty()
+            && self.settings.is_empty()
+            && self.ignore_targets.is_empty()
+            && self.names.is_empty()
+            && self.opted_out_users.is_empty()
+    }
+}
+
+/// `from`に保存されているすべてのデータを`to`にコピーし、コピーしたデータを返す
+/// `to`にデータが存在するときは失敗する
+pub async fn transfer(from: &dyn Storage, to: &dyn Storage) -> Result<Dump> {
+    if !to.export().await?.is_empty() {
+        bail!("The destination storage is not empty");
+    }
+
+    let dump = from.export().await?;
+    import(to, dump.clone()).await?;
+
+    Ok(dump)
+}
+
+async fn import(storage: &dyn Storage, dump: Dump) -> Result<()> {
+    for dict in dump.dicts {
+        for (word, read_as) in dict.entries {
+            storage
+                .insert_dict_word(dict::InsertOption {
+                    scope: dict.scope,
+                    word,
+                    read_as,
+                })
+                .await?;
+        }
+    }
+
+    for voice in dump.voices {
+        storage
+            .set_voice(voice::SetOption {
+                guild_id: voice.guild_id,
+                user_id: voice.user_id,
+                value: voice.preset_id,
+            })
+            .await?;
+    }
+
+    for setting in dump.settings {
+        storage
+            .set_setting(setting::SetOption {
+                guild_id: setting.guild_id,
+                setting: setting.setting,
+            })
+            .await?;
+    }
+
+    for ignore_target in dump.ignore_targets {
+        storage
+            .insert_ignore_target(ignore::InsertOption {
+                guild_id: ignore_target.guild_id,
+                target: ignore_target.target,
+            })
+            .await?;
+    }
+
+    for name in dump.names {
+        storage
+            .set_name(name::SetOption {
+                guild_id: name.guild_id,
+                user_id: name.user_id,
+                reading: name.reading,
+            })
+            .await?;
+    }
+
+    for user_id in dump.opted_out_users {
+        storage
+            .set_opt_out(user::SetOptOutOption {
+                user_id,
+                value: true,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
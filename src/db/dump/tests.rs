@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use super::transfer;
+use crate::db::{
+    Storage,
+    dict::{DictScope, GetAllOption, InsertOption},
+    ignore::IgnoreTarget,
+    memory::MemoryStorage,
+    setting::{FailureFeedback, GuildSetting},
+    sqlite::SqliteStorage,
+};
+
+const GUILD_ID: u64 = 1;
+const USER_ID: u64 = 10;
+
+/// すべての種類のデータを1つずつ保存する
+async fn fill(storage: &dyn Storage) {
+    storage
+        .insert_dict_word(InsertOption {
+            scope: DictScope::User {
+                guild_id: GUILD_ID,
+                user_id: USER_ID,
+            },
+            word: "koe".to_string(),
+            read_as: "こえ".to_string(),
+        })
+        .await
+        .unwrap();
+    storage
+        .set_voice(crate::db::voice::SetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            value: 2,
+        })
+        .await
+        .unwrap();
+    storage
+        .set_setting(crate::db::setting::SetOption {
+            guild_id: GUILD_ID,
+            setting: GuildSetting {
+                vc_members_only: true,
+                failure_feedback: FailureFeedback::Off,
+                ..GuildSetting::default()
+            },
+        })
+        .await
+        .unwrap();
+    storage
+        .insert_ignore_target(crate::db::ignore::InsertOption {
+            guild_id: GUILD_ID,
+            target: IgnoreTarget::Role(USER_ID),
+        })
+        .await
+        .unwrap();
+    storage
+        .set_name(crate::db::name::SetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            reading: "こえ".to_string(),
+        })
+        .await
+        .unwrap();
+    storage
+        .set_opt_out(crate::db::user::SetOptOutOption {
+            user_id: USER_ID,
+            value: true,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn transfer_copies_memory_to_sqlite() {
+    let source = MemoryStorage::default();
+    fill(&source).await;
+    let destination = SqliteStorage::open(Path::new(":memory:")).await.unwrap();
+
+    let dump = transfer(&source, &destination).await.unwrap();
+
+    assert_eq!(dump.dicts.len(), 1);
+    assert_eq!(dump.voices.len(), 1);
+    assert_eq!(dump.settings.len(), 1);
+    assert_eq!(dump.ignore_targets.len(), 1);
+    assert_eq!(dump.names.len(), 1);
+    assert_eq!(dump.opted_out_users, vec![USER_ID]);
+
+    let dict = destination
+        .get_dict(GetAllOption {
+            scope: DictScope::User {
+                guild_id: GUILD_ID,
+                user_id: USER_ID,
+            },
+        })
+        .await
+        .unwrap();
+    assert_eq!(dict, vec![("koe".to_string(), "こえ".to_string())]);
+    let voice = destination
+        .get_voice(crate::db::voice::GetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            fallback: 1,
+        })
+        .await
+        .unwrap();
+    assert_eq!(voice, 2);
+    let setting = destination
+        .get_setting(crate::db::setting::GetOption { guild_id: GUILD_ID })
+        .await
+        .unwrap();
+    assert!(setting.vc_members_only);
+    assert_eq!(setting.failure_feedback, FailureFeedback::Off);
+    let targets = destination
+        .get_ignore_targets(crate::db::ignore::GetAllOption { guild_id: GUILD_ID })
+        .await
+        .unwrap();
+    assert_eq!(targets, vec![IgnoreTarget::Role(USER_ID)]);
+    let names = destination
+        .get_names(crate::db::name::GetOption {
+            guild_id: GUILD_ID,
+            user_ids: vec![USER_ID],
+        })
+        .await
+        .unwrap();
+    assert_eq!(names, vec![Some("こえ".to_string())]);
+    let opted_out = destination
+        .get_opt_out(crate::db::user::GetOptOutOption { user_id: USER_ID })
+        .await
+        .unwrap();
+    assert!(opted_out);
+}
+
+#[tokio::test]
+async fn transfer_refuses_non_empty_destination() {
+    let source = MemoryStorage::default();
+    fill(&source).await;
+    let destination = SqliteStorage::open(Path::new(":memory:")).await.unwrap();
+    destination
+        .set_opt_out(crate::db::user::SetOptOutOption {
+            user_id: USER_ID + 1,
+            value: true,
+        })
+        .await
+        .unwrap();
+
+    let err = transfer(&source, &destination).await.unwrap_err();
+
+    assert_eq!(err.to_string(), "The destination storage is not empty");
+    // 移行先のデータは変更されない
+    let dump = destination.export().await.unwrap();
+    assert_eq!(dump.opted_out_users, vec![USER_ID + 1]);
+    assert!(dump.dicts.is_empty());
+}
@@ -0,0 +1,35 @@
+/// 読み上げない対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreTarget {
+    User(u64),
+    Role(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct InsertOption {
+    pub guild_id: u64,
+    pub target: IgnoreTarget,
+}
+
+#[derive(Debug, Clone)]
+pub enum InsertResponse {
+    Success,
+    AlreadyIgnored,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoveOption {
+    pub guild_id: u64,
+    pub target: IgnoreTarget,
+}
+
+#[derive(Debug, Clone)]
+pub enum RemoveResponse {
+    Success,
+    NotIgnored,
+}
+
+#[derive(Debug, Clone)]
+pub struct GetAllOption {
+    pub guild_id: u64,
+}
@@ -0,0 +1,319 @@
+//! メモリ上に保存する[`Storage`]の実装
+//!
+//! Koeを終了するとすべてのデータが失われる。動作確認用。
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::{
+    Storage,
+    dict::{self, DictScope},
+    dump::{DictDump, Dump, IgnoreTargetDump, NameDump, SettingDump, VoiceDump},
+    ignore::{self, IgnoreTarget},
+    name,
+    session::Session,
+    setting::{self, GuildSetting},
+    user, voice,
+};
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: Mutex<Data>,
+}
+
+#[derive(Default)]
+struct Data {
+    dicts: HashMap<DictScope, HashMap<String, String>>,
+    /// (サーバーのID, ユーザーのID) => 声
+    voices: HashMap<(u64, u64), i64>,
+    settings: HashMap<u64, GuildSetting>,
+    ignore_targets: HashMap<u64, Vec<IgnoreTarget>>,
+    /// (サーバーのID, ユーザーのID) => 名前の読み方
+    names: HashMap<(u64, u64), String>,
+    opted_out_users: HashSet<u64>,
+    sessions: Vec<Session>,
+    command_hash: Option<String>,
+}
+
+impl MemoryStorage {
+    fn data(&self) -> Result<MutexGuard<'_, Data>> {
+        self.data
+            .lock()
+            .map_err(|_| anyhow!("Memory storage mutex is poisoned"))
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn insert_dict_word(&self, option: dict::InsertOption) -> Result<dict::InsertResponse> {
+        let mut data = self.data()?;
+        let dict = data.dicts.entry(option.scope).or_default();
+
+        if dict.contains_key(&option.word) {
+            return Ok(dict::InsertResponse::WordAlreadyExists);
+        }
+        dict.insert(option.word, option.read_as);
+        Ok(dict::InsertResponse::Success)
+    }
+
+    async fn update_dict_word(&self, option: dict::UpdateOption) -> Result<dict::UpdateResponse> {
+        let mut data = self.data()?;
+
+        match data
+            .dicts
+            .get_mut(&option.scope)
+            .and_then(|dict| dict.get_mut(&option.word))
+        {
+            Some(read_as) => {
+                *read_as = option.read_as;
+                Ok(dict::UpdateResponse::Success)
+            }
+            None => Ok(dict::UpdateResponse::WordDoesNotExist),
+        }
+    }
+
+    async fn remove_dict_word(&self, option: dict::RemoveOption) -> Result<dict::RemoveResponse> {
+        let mut data = self.data()?;
+
+        let Some(dict) = data.dicts.get_mut(&option.scope) else {
+            return Ok(dict::RemoveResponse::WordDoesNotExist);
+        };
+        if dict.remove(&option.word).is_none() {
+            return Ok(dict::RemoveResponse::WordDoesNotExist);
+        }
+
+        // Redisと同様に、空の辞書は保存されていないものとして扱う
+        if dict.is_empty() {
+            data.dicts.remove(&option.scope);
+        }
+        Ok(dict::RemoveResponse::Success)
+    }
+
+    async fn get_dict(&self, option: dict::GetAllOption) -> Result<Vec<(String, String)>> {
+        let data = self.data()?;
+
+        let dict = data
+            .dicts
+            .get(&option.scope)
+            .map(|dict| dict.clone().into_iter().collect())
+            .unwrap_or_default();
+        Ok(dict)
+    }
+
+    async fn get_voice(&self, option: voice::GetOption) -> Result<i64> {
+        let mut data = self.data()?;
+
+        let voice = data
+            .voices
+            .entry((option.guild_id, option.user_id))
+            .or_insert(option.fallback);
+        Ok(*voice)
+    }
+
+    async fn set_voice(&self, option: voice::SetOption) -> Result<()> {
+        let mut data = self.data()?;
+        data.voices
+            .insert((option.guild_id, option.user_id), option.value);
+        Ok(())
+    }
+
+    async fn get_setting(&self, option: setting::GetOption) -> Result<GuildSetting> {
+        let data = self.data()?;
+
+        let setting = data
+            .settings
+            .get(&option.guild_id)
+            .cloned()
+            .unwrap_or_default();
+        Ok(setting)
+    }
+
+    async fn set_setting(&self, option: setting::SetOption) -> Result<()> {
+        let mut data = self.data()?;
+        data.settings.insert(option.guild_id, option.setting);
+        Ok(())
+    }
+
+    async fn insert_ignore_target(
+        &self,
+        option: ignore::InsertOption,
+    ) -> Result<ignore::InsertResponse> {
+        let mut data = self.data()?;
+        let targets = data.ignore_targets.entry(option.guild_id).or_default();
+
+        if targets.contains(&option.target) {
+            return Ok(ignore::InsertResponse::AlreadyIgnored);
+        }
+        targets.push(option.target);
+        Ok(ignore::InsertResponse::Success)
+    }
+
+    async fn remove_ignore_target(
+        &self,
+        option: ignore::RemoveOption,
+    ) -> Result<ignore::RemoveResponse> {
+        let mut data = self.data()?;
+        let targets = data.ignore_targets.entry(option.guild_id).or_default();
+
+        let Some(idx) = targets.iter().position(|target| *target == option.target) else {
+            return Ok(ignore::RemoveResponse::NotIgnored);
+        };
+        targets.remove(idx);
+        Ok(ignore::RemoveResponse::Success)
+    }
+
+    async fn get_ignore_targets(&self, option: ignore::GetAllOption) -> Result<Vec<IgnoreTarget>> {
+        let data = self.data()?;
+
+        let targets = data
+            .ignore_targets
+            .get(&option.guild_id)
+            .cloned()
+            .unwrap_or_default();
+        Ok(targets)
+    }
+
+    async fn get_names(&self, option: name::GetOption) -> Result<Vec<Option<String>>> {
+        let data = self.data()?;
+
+        let names = option
+            .user_ids
+            .iter()
+            .map(|user_id| data.names.get(&(option.guild_id, *user_id)).cloned())
+            .collect();
+        Ok(names)
+    }
+
+    async fn set_name(&self, option: name::SetOption) -> Result<()> {
+        let mut data = self.data()?;
+        data.names
+            .insert((option.guild_id, option.user_id), option.reading);
+        Ok(())
+    }
+
+    async fn remove_name(&self, option: name::RemoveOption) -> Result<name::RemoveResponse> {
+        let mut data = self.data()?;
+
+        Ok(
+            match data.names.remove(&(option.guild_id, option.user_id)) {
+                Some(_) => name::RemoveResponse::Success,
+                None => name::RemoveResponse::ReadingDoesNotExist,
+            },
+        )
+    }
+
+    async fn get_opt_out(&self, option: user::GetOptOutOption) -> Result<bool> {
+        let data = self.data()?;
+        Ok(data.opted_out_users.contains(&option.user_id))
+    }
+
+    async fn set_opt_out(&self, option: user::SetOptOutOption) -> Result<()> {
+        let mut data = self.data()?;
+
+        if option.value {
+            data.opted_out_users.insert(option.user_id);
+        } else {
+            data.opted_out_users.remove(&option.user_id);
+        }
+        Ok(())
+    }
+
+    async fn delete_guild_user_data(&self, option: user::DeleteGuildDataOption) -> Result<usize> {
+        let mut data = self.data()?;
+        let user_id = option.user_id;
+
+        let dicts_before = data.dicts.len();
+        data.dicts.retain(
+            |scope, _| !matches!(scope, DictScope::User { user_id: id, .. } if *id == user_id),
+        );
+        let voices_before = data.voices.len();
+        data.voices.retain(|(_, id), _| *id != user_id);
+        let names_before = data.names.len();
+        data.names.retain(|(_, id), _| *id != user_id);
+
+        Ok((dicts_before - data.dicts.len())
+            + (voices_before - data.voices.len())
+            + (names_before - data.names.len()))
+    }
+
+    async fn export(&self) -> Result<Dump> {
+        let data = self.data()?;
+
+        let dump = Dump {
+            dicts: data
+                .dicts
+                .iter()
+                .map(|(scope, entries)| DictDump {
+                    scope: *scope,
+                    entries: entries.clone().into_iter().collect(),
+                })
+                .collect(),
+            voices: data
+                .voices
+                .iter()
+                .map(|((guild_id, user_id), preset_id)| VoiceDump {
+                    guild_id: *guild_id,
+                    user_id: *user_id,
+                    preset_id: *preset_id,
+                })
+                .collect(),
+            settings: data
+                .settings
+                .iter()
+                .map(|(guild_id, setting)| SettingDump {
+                    guild_id: *guild_id,
+                    setting: setting.clone(),
+                })
+                .collect(),
+            ignore_targets: data
+                .ignore_targets
+                .iter()
+                .flat_map(|(guild_id, targets)| {
+                    targets.iter().map(|target| IgnoreTargetDump {
+                        guild_id: *guild_id,
+                        target: *target,
+                    })
+                })
+                .collect(),
+            names: data
+                .names
+                .iter()
+                .map(|((guild_id, user_id), reading)| NameDump {
+                    guild_id: *guild_id,
+                    user_id: *user_id,
+                    reading: reading.clone(),
+                })
+                .collect(),
+            opted_out_users: data.opted_out_users.iter().copied().collect(),
+        };
+        Ok(dump)
+    }
+
+    async fn save_sessions(&self, sessions: Vec<Session>) -> Result<()> {
+        self.data()?.sessions = sessions;
+        Ok(())
+    }
+
+    async fn take_sessions(&self) -> Result<Vec<Session>> {
+        Ok(std::mem::take(&mut self.data()?.sessions))
+    }
+
+    async fn get_command_hash(&self) -> Result<Option<String>> {
+        Ok(self.data()?.command_hash.clone())
+    }
+
+    async fn set_command_hash(&self, hash: Option<String>) -> Result<()> {
+        self.data()?.command_hash = hash;
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<()> {
+        // メモリ上のデータには常にアクセスできる
+        Ok(())
+    }
+}
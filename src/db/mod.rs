@@ -1,2 +1,148 @@
+//! 設定や辞書などの永続化されるデータ
+//!
+//! データの保存先は[`Storage`]トレイトで抽象化されており、設定ファイルで選択できる。
+
+use std::{fmt, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::config::{Config, StorageBackend};
+
 pub mod dict;
+pub mod dump;
+pub mod ignore;
+pub mod memory;
+pub mod name;
+pub mod redis;
+pub mod session;
+pub mod setting;
+pub mod sqlite;
+pub mod user;
 pub mod voice;
+
+/// 保存先に接続できないことを表すエラー
+///
+/// 一時的な障害であることが多いため、ユーザーに再試行を促すために使う。
+#[derive(Debug)]
+pub struct StorageUnavailable;
+
+impl fmt::Display for StorageUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Storage is unavailable")
+    }
+}
+
+/// データの保存先
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// 辞書に語句を追加する
+    async fn insert_dict_word(&self, option: dict::InsertOption) -> Result<dict::InsertResponse>;
+
+    /// 辞書に登録されている語句の読み方を変更する
+    async fn update_dict_word(&self, option: dict::UpdateOption) -> Result<dict::UpdateResponse>;
+
+    /// 辞書から語句を削除する
+    async fn remove_dict_word(&self, option: dict::RemoveOption) -> Result<dict::RemoveResponse>;
+
+    /// 辞書全体を返す
+    /// 辞書が存在しないときは空の[`Vec`]を返す
+    async fn get_dict(&self, option: dict::GetAllOption) -> Result<Vec<(String, String)>>;
+
+    /// ユーザーの声を返す
+    /// 未設定の場合は`option.fallback`の値を設定して返す
+    async fn get_voice(&self, option: voice::GetOption) -> Result<i64>;
+
+    /// ユーザーの声を設定する
+    async fn set_voice(&self, option: voice::SetOption) -> Result<()>;
+
+    /// サーバーの設定を返す
+    /// 未設定の項目はデフォルト値を返す
+    async fn get_setting(&self, option: setting::GetOption) -> Result<setting::GuildSetting>;
+
+    /// サーバーの設定を保存する
+    async fn set_setting(&self, option: setting::SetOption) -> Result<()>;
+
+    /// 読み上げない対象を追加する
+    async fn insert_ignore_target(
+        &self,
+        option: ignore::InsertOption,
+    ) -> Result<ignore::InsertResponse>;
+
+    /// 読み上げない対象を削除する
+    async fn remove_ignore_target(
+        &self,
+        option: ignore::RemoveOption,
+    ) -> Result<ignore::RemoveResponse>;
+
+    /// 読み上げない対象をすべて返す
+    async fn get_ignore_targets(
+        &self,
+        option: ignore::GetAllOption,
+    ) -> Result<Vec<ignore::IgnoreTarget>>;
+
+    /// ユーザーの名前の読み方を返す
+    /// 未設定のユーザーについては[`None`]を返す
+    async fn get_names(&self, option: name::GetOption) -> Result<Vec<Option<String>>>;
+
+    /// ユーザーの名前の読み方を設定する
+    async fn set_name(&self, option: name::SetOption) -> Result<()>;
+
+    /// ユーザーの名前の読み方を削除する
+    async fn remove_name(&self, option: name::RemoveOption) -> Result<name::RemoveResponse>;
+
+    /// ユーザーがメッセージの読み上げを拒否しているかを返す
+    async fn get_opt_out(&self, option: user::GetOptOutOption) -> Result<bool>;
+
+    /// ユーザーがメッセージの読み上げを拒否しているかを設定する
+    async fn set_opt_out(&self, option: user::SetOptOutOption) -> Result<()>;
+
+    /// すべてのサーバーに保存されているユーザーのデータを削除する
+    /// 削除した設定（辞書、声、名前の読み方）の数を返す
+    async fn delete_guild_user_data(&self, option: user::DeleteGuildDataOption) -> Result<usize>;
+
+    /// 保存されているすべてのデータを返す
+    async fn export(&self) -> Result<dump::Dump>;
+
+    /// 接続中のボイスチャンネルを保存する
+    /// 以前に保存されていたものはすべて置き換える
+    async fn save_sessions(&self, sessions: Vec<session::Session>) -> Result<()>;
+
+    /// 保存されているボイスチャンネルを返し、削除する
+    async fn take_sessions(&self) -> Result<Vec<session::Session>>;
+
+    /// グローバルに登録したコマンドの定義のハッシュを返す
+    /// 登録していないときは`None`を返す
+    async fn get_command_hash(&self) -> Result<Option<String>>;
+
+    /// グローバルに登録したコマンドの定義のハッシュを保存する
+    /// `None`のときは削除する
+    async fn set_command_hash(&self, hash: Option<String>) -> Result<()>;
+
+    /// 保存先に接続できるかを確認する
+    async fn ping(&self) -> Result<()>;
+}
+
+/// 設定ファイルで指定された保存先に接続する
+pub async fn connect(config: &Config, backend: StorageBackend) -> Result<Arc<dyn Storage>> {
+    let storage: Arc<dyn Storage> = match backend {
+        StorageBackend::Redis => {
+            let redis_config = config
+                .redis
+                .as_ref()
+                .context("`redis` must be configured to use Redis as the storage backend")?;
+            Arc::new(redis::RedisStorage::connect(redis_config).await?)
+        }
+        StorageBackend::Sqlite => {
+            let sqlite_config = config.storage.sqlite.as_ref().context(
+                "`storage.sqlite` must be configured to use SQLite as the storage backend",
+            )?;
+            Arc::new(sqlite::SqliteStorage::open(&sqlite_config.path).await?)
+        }
+        StorageBackend::Memory => Arc::new(memory::MemoryStorage::default()),
+    };
+
+    info!("Connected to {backend} storage");
+    Ok(storage)
+}
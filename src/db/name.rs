@@ -0,0 +1,24 @@
+#[derive(Debug, Clone)]
+pub struct GetOption {
+    pub guild_id: u64,
+    pub user_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetOption {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub reading: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoveOption {
+    pub guild_id: u64,
+    pub user_id: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum RemoveResponse {
+    Success,
+    ReadingDoesNotExist,
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::key;
+
+/// グローバルに登録したコマンドの定義のハッシュを返す
+pub async fn get_hash(connection: &mut ConnectionManager) -> Result<Option<String>> {
+    let hash = connection.get(key::command_hash()).await?;
+    Ok(hash)
+}
+
+/// グローバルに登録したコマンドの定義のハッシュを保存する
+/// `None`のときは削除する
+pub async fn set_hash(connection: &mut ConnectionManager, hash: Option<String>) -> Result<()> {
+    let () = match hash {
+        Some(hash) => connection.set(key::command_hash(), hash).await?,
+        None => connection.del(key::command_hash()).await?,
+    };
+    Ok(())
+}
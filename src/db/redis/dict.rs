@@ -0,0 +1,88 @@
+use std::sync::LazyLock;
+
+use anyhow::{Result, bail};
+use redis::{AsyncCommands, Script, aio::ConnectionManager};
+
+use super::key;
+use crate::db::dict::{
+    DictScope, GetAllOption, InsertOption, InsertResponse, RemoveOption, RemoveResponse,
+    UpdateOption, UpdateResponse,
+};
+
+/// 辞書に語句を追加する
+pub async fn insert(
+    connection: &mut ConnectionManager,
+    option: InsertOption,
+) -> Result<InsertResponse> {
+    let resp = connection
+        .hset_nx(dict_key(option.scope), option.word, option.read_as)
+        .await?;
+
+    Ok(match resp {
+        0 => InsertResponse::WordAlreadyExists,
+        1 => InsertResponse::Success,
+        x => bail!("Unknown HSETNX response from Redis: {x}"),
+    })
+}
+
+/// 辞書に登録されている語句の読み方を変更する
+pub async fn update(
+    connection: &mut ConnectionManager,
+    option: UpdateOption,
+) -> Result<UpdateResponse> {
+    static SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+        Script::new(
+            r"
+            if redis.call('HEXISTS', KEYS[1], ARGV[1]) == 0 then
+                return 0
+            end
+            redis.call('HSET', KEYS[1], ARGV[1], ARGV[2])
+            return 1
+            ",
+        )
+    });
+
+    let resp = SCRIPT
+        .key(dict_key(option.scope))
+        .arg(option.word)
+        .arg(option.read_as)
+        .invoke_async(connection)
+        .await?;
+
+    Ok(match resp {
+        0 => UpdateResponse::WordDoesNotExist,
+        1 => UpdateResponse::Success,
+        x => bail!("Unknown response from Redis script: {x}"),
+    })
+}
+
+/// 辞書から語句を削除する
+pub async fn remove(
+    connection: &mut ConnectionManager,
+    option: RemoveOption,
+) -> Result<RemoveResponse> {
+    let resp = connection.hdel(dict_key(option.scope), option.word).await?;
+
+    Ok(match resp {
+        0 => RemoveResponse::WordDoesNotExist,
+        1 => RemoveResponse::Success,
+        x => bail!("Unknown HDEL response from Redis: {x}"),
+    })
+}
+
+/// 辞書全体を返す
+/// 辞書が存在しないときは空の[`Vec`]を返す
+pub async fn get_all(
+    connection: &mut ConnectionManager,
+    option: GetAllOption,
+) -> Result<Vec<(String, String)>> {
+    let resp = connection.hgetall(dict_key(option.scope)).await?;
+    Ok(resp)
+}
+
+fn dict_key(scope: DictScope) -> String {
+    match scope {
+        DictScope::Guild { guild_id } => key::guild_dict(guild_id),
+        DictScope::User { guild_id, user_id } => key::guild_user_dict(guild_id, user_id),
+    }
+}
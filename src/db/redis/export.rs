@@ -0,0 +1,90 @@
+use anyhow::{Context as _, Result};
+use redis::{AsyncCommands, aio::ConnectionManager};
+use tracing::warn;
+
+use super::{dict, setting};
+use crate::db::{
+    dict::{DictScope, GetAllOption},
+    dump::{DictDump, Dump, IgnoreTargetDump, NameDump, SettingDump, VoiceDump},
+    ignore::IgnoreTarget,
+};
+
+/// 保存されているすべてのデータを返す
+pub async fn export(connection: &mut ConnectionManager) -> Result<Dump> {
+    let keys = {
+        let mut iter = connection.scan::<String>().await?;
+
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key?);
+        }
+        keys
+    };
+
+    let mut dump = Dump::default();
+    for key in keys {
+        let parts = key.split(':').collect::<Vec<_>>();
+
+        match parts[..] {
+            ["guild", guild_id, "dict"] => {
+                let scope = DictScope::Guild {
+                    guild_id: parse_id(guild_id, &key)?,
+                };
+                let entries = dict::get_all(connection, GetAllOption { scope }).await?;
+                dump.dicts.push(DictDump { scope, entries });
+            }
+            ["guild", guild_id, "setting"] => {
+                let guild_id = parse_id(guild_id, &key)?;
+                let setting =
+                    setting::get(connection, crate::db::setting::GetOption { guild_id }).await?;
+                dump.settings.push(SettingDump { guild_id, setting });
+            }
+            ["guild", guild_id, "ignore", kind @ ("users" | "roles")] => {
+                let guild_id = parse_id(guild_id, &key)?;
+                let ids: Vec<u64> = connection.smembers(&key).await?;
+                dump.ignore_targets.extend(ids.into_iter().map(|id| {
+                    let target = if kind == "users" {
+                        IgnoreTarget::User(id)
+                    } else {
+                        IgnoreTarget::Role(id)
+                    };
+                    IgnoreTargetDump { guild_id, target }
+                }));
+            }
+            ["guild", guild_id, "user", user_id, "dict"] => {
+                let scope = DictScope::User {
+                    guild_id: parse_id(guild_id, &key)?,
+                    user_id: parse_id(user_id, &key)?,
+                };
+                let entries = dict::get_all(connection, GetAllOption { scope }).await?;
+                dump.dicts.push(DictDump { scope, entries });
+            }
+            ["guild", guild_id, "user", user_id, "voice"] => {
+                dump.voices.push(VoiceDump {
+                    guild_id: parse_id(guild_id, &key)?,
+                    user_id: parse_id(user_id, &key)?,
+                    preset_id: connection.get(&key).await?,
+                });
+            }
+            ["guild", guild_id, "user", user_id, "name"] => {
+                dump.names.push(NameDump {
+                    guild_id: parse_id(guild_id, &key)?,
+                    user_id: parse_id(user_id, &key)?,
+                    reading: connection.get(&key).await?,
+                });
+            }
+            ["user", user_id, "optout"] => {
+                dump.opted_out_users.push(parse_id(user_id, &key)?);
+            }
+            ["koe", ..] => {}
+            _ => warn!("Skipping unknown key: {key}"),
+        }
+    }
+
+    Ok(dump)
+}
+
+fn parse_id(id: &str, key: &str) -> Result<u64> {
+    id.parse()
+        .with_context(|| format!("Invalid ID in key: {key}"))
+}
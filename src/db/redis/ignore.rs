@@ -0,0 +1,64 @@
+use anyhow::{Result, bail};
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::key;
+use crate::db::ignore::{
+    GetAllOption, IgnoreTarget, InsertOption, InsertResponse, RemoveOption, RemoveResponse,
+};
+
+/// 読み上げない対象を追加する
+pub async fn insert(
+    connection: &mut ConnectionManager,
+    option: InsertOption,
+) -> Result<InsertResponse> {
+    let (key, id) = target_key_and_id(option.guild_id, option.target);
+    let resp = connection.sadd(key, id).await?;
+
+    Ok(match resp {
+        0 => InsertResponse::AlreadyIgnored,
+        1 => InsertResponse::Success,
+        x => bail!("Unknown SADD response from Redis: {x}"),
+    })
+}
+
+/// 読み上げない対象を削除する
+pub async fn remove(
+    connection: &mut ConnectionManager,
+    option: RemoveOption,
+) -> Result<RemoveResponse> {
+    let (key, id) = target_key_and_id(option.guild_id, option.target);
+    let resp = connection.srem(key, id).await?;
+
+    Ok(match resp {
+        0 => RemoveResponse::NotIgnored,
+        1 => RemoveResponse::Success,
+        x => bail!("Unknown SREM response from Redis: {x}"),
+    })
+}
+
+/// 読み上げない対象をすべて返す
+pub async fn get_all(
+    connection: &mut ConnectionManager,
+    option: GetAllOption,
+) -> Result<Vec<IgnoreTarget>> {
+    let (users, roles): (Vec<u64>, Vec<u64>) = redis::pipe()
+        .smembers(key::guild_ignored_users(option.guild_id))
+        .smembers(key::guild_ignored_roles(option.guild_id))
+        .query_async(connection)
+        .await?;
+
+    let targets = users
+        .into_iter()
+        .map(IgnoreTarget::User)
+        .chain(roles.into_iter().map(IgnoreTarget::Role))
+        .collect();
+
+    Ok(targets)
+}
+
+fn target_key_and_id(guild_id: u64, target: IgnoreTarget) -> (String, u64) {
+    match target {
+        IgnoreTarget::User(user_id) => (key::guild_ignored_users(guild_id), user_id),
+        IgnoreTarget::Role(role_id) => (key::guild_ignored_roles(guild_id), role_id),
+    }
+}
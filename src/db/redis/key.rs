@@ -0,0 +1,68 @@
+//! Redisのキーの一覧
+//!
+//! キーの形式を変更する場合は、[`super::migration`]にマイグレーションを追加すること。
+
+/// データベースのスキーマのバージョン
+pub fn schema_version() -> String {
+    "koe:schema_version".to_string()
+}
+
+/// マイグレーションの多重実行を防ぐためのロック
+pub fn migration_lock() -> String {
+    "koe:migration_lock".to_string()
+}
+
+/// 終了時に接続していたボイスチャンネル
+pub fn sessions() -> String {
+    "koe:sessions".to_string()
+}
+
+/// グローバルに登録したコマンドの定義のハッシュ
+pub fn command_hash() -> String {
+    "koe:command_hash".to_string()
+}
+
+/// サーバーの辞書
+pub fn guild_dict(guild_id: u64) -> String {
+    format!("guild:{guild_id}:dict")
+}
+
+/// サーバーの設定
+pub fn guild_setting(guild_id: u64) -> String {
+    format!("guild:{guild_id}:setting")
+}
+
+/// サーバーで読み上げないユーザーの集合
+pub fn guild_ignored_users(guild_id: u64) -> String {
+    format!("guild:{guild_id}:ignore:users")
+}
+
+/// サーバーで読み上げないロールの集合
+pub fn guild_ignored_roles(guild_id: u64) -> String {
+    format!("guild:{guild_id}:ignore:roles")
+}
+
+/// サーバー内のユーザーの辞書
+pub fn guild_user_dict(guild_id: u64, user_id: u64) -> String {
+    format!("guild:{guild_id}:user:{user_id}:dict")
+}
+
+/// サーバー内のユーザーの声
+pub fn guild_user_voice(guild_id: u64, user_id: u64) -> String {
+    format!("guild:{guild_id}:user:{user_id}:voice")
+}
+
+/// サーバー内のユーザーの名前の読み方
+pub fn guild_user_name(guild_id: u64, user_id: u64) -> String {
+    format!("guild:{guild_id}:user:{user_id}:name")
+}
+
+/// すべてのサーバーにおけるユーザーのデータにマッチするパターン
+pub fn guild_user_data_pattern(user_id: u64) -> String {
+    format!("guild:*:user:{user_id}:*")
+}
+
+/// ユーザーが読み上げを拒否しているか
+pub fn user_opt_out(user_id: u64) -> String {
+    format!("user:{user_id}:optout")
+}
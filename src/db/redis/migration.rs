@@ -0,0 +1,126 @@
+use anyhow::{Context as _, Result, bail};
+use redis::{AsyncTypedCommands, aio::ConnectionManager};
+use tracing::info;
+
+use super::key;
+
+/// データベースに対するマイグレーション
+#[derive(Debug)]
+pub struct Migration {
+    /// 適用後のスキーマのバージョン
+    pub version: u32,
+    pub description: &'static str,
+}
+
+/// すべてのマイグレーション
+/// 新しいマイグレーションは末尾に追加し、[`apply`]に処理を実装すること
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Introduce schema version. Existing keys already conform to this version.",
+}];
+
+/// 現在のスキーマのバージョン
+pub const LATEST_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// マイグレーションのロックの有効期限（秒）
+const LOCK_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Debug, Clone)]
+pub struct RunOption {
+    /// `true`のときはマイグレーションを適用せず、適用されるマイグレーションを返すのみとする
+    pub dry_run: bool,
+}
+
+/// 未適用のマイグレーションを順に適用し、適用したマイグレーションを返す
+pub async fn run(
+    connection: &mut ConnectionManager,
+    option: RunOption,
+) -> Result<Vec<&'static Migration>> {
+    let current_version = get_version(connection).await?;
+    if current_version > LATEST_VERSION {
+        bail!(
+            "Database schema version {current_version} is newer than the latest version supported \
+             by this build ({LATEST_VERSION})"
+        );
+    }
+
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current_version)
+        .collect::<Vec<_>>();
+
+    if option.dry_run || pending.is_empty() {
+        return Ok(pending);
+    }
+
+    let locked = connection
+        .set_options(
+            key::migration_lock(),
+            1,
+            redis::SetOptions::default()
+                .conditional_set(redis::ExistenceCheck::NX)
+                .with_expiration(redis::SetExpiry::EX(LOCK_TIMEOUT_SECS)),
+        )
+        .await?
+        .is_some();
+    if !locked {
+        bail!("Another process is running migrations");
+    }
+
+    let result = apply_all(connection, &pending).await;
+
+    connection
+        .del(key::migration_lock())
+        .await
+        .context("Failed to release migration lock")?;
+
+    result.map(|()| pending)
+}
+
+/// データベースのスキーマのバージョンを返す
+/// バージョンが記録されていないときは0を返す
+pub async fn get_version(connection: &mut ConnectionManager) -> Result<u32> {
+    let resp = connection.get(key::schema_version()).await?;
+
+    let version = match resp {
+        Some(version) => version
+            .parse()
+            .with_context(|| format!("Invalid schema version: {version}"))?,
+        None => 0,
+    };
+
+    Ok(version)
+}
+
+async fn apply_all(connection: &mut ConnectionManager, pending: &[&Migration]) -> Result<()> {
+    for migration in pending {
+        info!(
+            "Applying migration to schema version {}: {}",
+            migration.version, migration.description
+        );
+
+        apply(connection, migration.version)
+            .await
+            .with_context(|| {
+                format!("Failed to migrate to schema version {}", migration.version)
+            })?;
+
+        connection
+            .set(key::schema_version(), migration.version)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[expect(
+    clippy::unused_async,
+    reason = "migrations other than the first one will access Redis"
+)]
+async fn apply(_connection: &mut ConnectionManager, version: u32) -> Result<()> {
+    match version {
+        // スキーマのバージョンを記録するのみ
+        1 => Ok(()),
+        _ => bail!("Migration to schema version {version} is not implemented"),
+    }
+}
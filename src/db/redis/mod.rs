@@ -0,0 +1,276 @@
+//! Redisによる[`Storage`]の実装
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use redis::{
+    RedisError,
+    aio::{ConnectionManager, ConnectionManagerConfig},
+};
+use tracing::info;
+
+use crate::{
+    config::RedisConfig,
+    db::{self, Storage, StorageUnavailable, dump::Dump},
+    metrics,
+};
+
+mod command;
+mod dict;
+mod export;
+mod ignore;
+mod key;
+pub mod migration;
+mod name;
+mod session;
+mod setting;
+#[cfg(test)]
+mod tests;
+mod user;
+mod voice;
+
+pub struct RedisStorage {
+    /// 接続が切れたときに自動的に再接続する。複製しても同じ接続を共有する
+    connection: ConnectionManager,
+}
+
+impl RedisStorage {
+    /// Redisに接続し、未適用のマイグレーションを適用する
+    pub async fn connect(config: &RedisConfig) -> Result<Self> {
+        let mut connection = connect(config).await?;
+
+        let applied = migration::run(&mut connection, migration::RunOption { dry_run: false })
+            .await
+            .context("Failed to migrate database")?;
+        info!(
+            "Database schema is up to date (version {}, {} migrations applied)",
+            migration::LATEST_VERSION,
+            applied.len()
+        );
+
+        Ok(Self { connection })
+    }
+
+    fn connection(&self) -> ConnectionManager {
+        self.connection.clone()
+    }
+}
+
+/// 設定に従ってRedisに接続する
+pub async fn connect(config: &RedisConfig) -> Result<ConnectionManager> {
+    let client = redis::Client::open(config.url.as_str())?;
+    let manager_config = ConnectionManagerConfig::new()
+        .set_connection_timeout(Some(Duration::from_millis(config.connection_timeout_ms)))
+        .set_response_timeout(Some(Duration::from_millis(config.response_timeout_ms)))
+        .set_number_of_retries(config.max_retries);
+
+    let connection = client
+        .get_connection_manager_with_config(manager_config)
+        .await
+        .context("Failed to connect to Redis")?;
+
+    Ok(connection)
+}
+
+/// Redisに接続できないことによるエラーであれば、[`StorageUnavailable`]として扱う
+fn classify_error(err: anyhow::Error) -> anyhow::Error {
+    let is_unavailable = err.downcast_ref::<RedisError>().is_some_and(|err| {
+        err.is_io_error()
+            || err.is_timeout()
+            || err.is_connection_dropped()
+            || err.is_connection_refusal()
+    });
+
+    if is_unavailable {
+        err.context(StorageUnavailable)
+    } else {
+        err
+    }
+}
+
+/// 操作にかかった時間をメトリクスに記録し、エラーを分類する
+async fn observe<T>(operation: &'static str, fut: impl Future<Output = Result<T>>) -> Result<T> {
+    let started_at = Instant::now();
+    let result = fut.await.map_err(classify_error);
+    metrics::observe_redis(operation, started_at.elapsed());
+
+    result
+}
+
+#[async_trait]
+impl Storage for RedisStorage {
+    async fn insert_dict_word(
+        &self,
+        option: db::dict::InsertOption,
+    ) -> Result<db::dict::InsertResponse> {
+        observe(
+            "insert_dict_word",
+            dict::insert(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn update_dict_word(
+        &self,
+        option: db::dict::UpdateOption,
+    ) -> Result<db::dict::UpdateResponse> {
+        observe(
+            "update_dict_word",
+            dict::update(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn remove_dict_word(
+        &self,
+        option: db::dict::RemoveOption,
+    ) -> Result<db::dict::RemoveResponse> {
+        observe(
+            "remove_dict_word",
+            dict::remove(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn get_dict(&self, option: db::dict::GetAllOption) -> Result<Vec<(String, String)>> {
+        observe("get_dict", dict::get_all(&mut self.connection(), option)).await
+    }
+
+    async fn get_voice(&self, option: db::voice::GetOption) -> Result<i64> {
+        observe("get_voice", voice::get(&mut self.connection(), option)).await
+    }
+
+    async fn set_voice(&self, option: db::voice::SetOption) -> Result<()> {
+        observe("set_voice", voice::set(&mut self.connection(), option)).await
+    }
+
+    async fn get_setting(
+        &self,
+        option: db::setting::GetOption,
+    ) -> Result<db::setting::GuildSetting> {
+        observe("get_setting", setting::get(&mut self.connection(), option)).await
+    }
+
+    async fn set_setting(&self, option: db::setting::SetOption) -> Result<()> {
+        observe("set_setting", setting::set(&mut self.connection(), option)).await
+    }
+
+    async fn insert_ignore_target(
+        &self,
+        option: db::ignore::InsertOption,
+    ) -> Result<db::ignore::InsertResponse> {
+        observe(
+            "insert_ignore_target",
+            ignore::insert(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn remove_ignore_target(
+        &self,
+        option: db::ignore::RemoveOption,
+    ) -> Result<db::ignore::RemoveResponse> {
+        observe(
+            "remove_ignore_target",
+            ignore::remove(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn get_ignore_targets(
+        &self,
+        option: db::ignore::GetAllOption,
+    ) -> Result<Vec<db::ignore::IgnoreTarget>> {
+        observe(
+            "get_ignore_targets",
+            ignore::get_all(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn get_names(&self, option: db::name::GetOption) -> Result<Vec<Option<String>>> {
+        observe("get_names", name::get(&mut self.connection(), option)).await
+    }
+
+    async fn set_name(&self, option: db::name::SetOption) -> Result<()> {
+        observe("set_name", name::set(&mut self.connection(), option)).await
+    }
+
+    async fn remove_name(
+        &self,
+        option: db::name::RemoveOption,
+    ) -> Result<db::name::RemoveResponse> {
+        observe("remove_name", name::remove(&mut self.connection(), option)).await
+    }
+
+    async fn get_opt_out(&self, option: db::user::GetOptOutOption) -> Result<bool> {
+        observe(
+            "get_opt_out",
+            user::get_opt_out(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn set_opt_out(&self, option: db::user::SetOptOutOption) -> Result<()> {
+        observe(
+            "set_opt_out",
+            user::set_opt_out(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn delete_guild_user_data(
+        &self,
+        option: db::user::DeleteGuildDataOption,
+    ) -> Result<usize> {
+        observe(
+            "delete_guild_user_data",
+            user::delete_guild_data(&mut self.connection(), option),
+        )
+        .await
+    }
+
+    async fn export(&self) -> Result<Dump> {
+        observe("export", export::export(&mut self.connection())).await
+    }
+
+    async fn save_sessions(&self, sessions: Vec<db::session::Session>) -> Result<()> {
+        observe(
+            "save_sessions",
+            session::save(&mut self.connection(), sessions),
+        )
+        .await
+    }
+
+    async fn take_sessions(&self) -> Result<Vec<db::session::Session>> {
+        observe("take_sessions", session::take(&mut self.connection())).await
+    }
+
+    async fn get_command_hash(&self) -> Result<Option<String>> {
+        observe(
+            "get_command_hash",
+            command::get_hash(&mut self.connection()),
+        )
+        .await
+    }
+
+    async fn set_command_hash(&self, hash: Option<String>) -> Result<()> {
+        observe(
+            "set_command_hash",
+            command::set_hash(&mut self.connection(), hash),
+        )
+        .await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        let mut connection = self.connection();
+        observe("ping", async move {
+            redis::cmd("PING")
+                .query_async::<()>(&mut connection)
+                .await
+                .map_err(anyhow::Error::from)
+        })
+        .await
+    }
+}
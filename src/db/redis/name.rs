@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::key;
+use crate::db::name::{GetOption, RemoveOption, RemoveResponse, SetOption};
+
+/// ユーザーの名前の読み方を返す
+/// 未設定のユーザーについては[`None`]を返す
+pub async fn get(
+    connection: &mut ConnectionManager,
+    option: GetOption,
+) -> Result<Vec<Option<String>>> {
+    if option.user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let keys = option
+        .user_ids
+        .iter()
+        .map(|user_id| key::guild_user_name(option.guild_id, *user_id))
+        .collect::<Vec<_>>();
+
+    let resp = redis::cmd("MGET").arg(keys).query_async(connection).await?;
+    Ok(resp)
+}
+
+/// ユーザーの名前の読み方を設定する
+pub async fn set(connection: &mut ConnectionManager, option: SetOption) -> Result<()> {
+    let () = connection
+        .set(
+            key::guild_user_name(option.guild_id, option.user_id),
+            option.reading,
+        )
+        .await?;
+    Ok(())
+}
+
+/// ユーザーの名前の読み方を削除する
+pub async fn remove(
+    connection: &mut ConnectionManager,
+    option: RemoveOption,
+) -> Result<RemoveResponse> {
+    let resp = connection
+        .del(key::guild_user_name(option.guild_id, option.user_id))
+        .await?;
+
+    Ok(match resp {
+        0 => RemoveResponse::ReadingDoesNotExist,
+        1 => RemoveResponse::Success,
+        x => bail!("Unknown DEL response from Redis: {x}"),
+    })
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context as _, Result};
+use redis::aio::ConnectionManager;
+
+use super::key;
+use crate::db::session::Session;
+
+/// 接続中のボイスチャンネルを保存する
+/// 以前に保存されていたものはすべて置き換える
+pub async fn save(connection: &mut ConnectionManager, sessions: Vec<Session>) -> Result<()> {
+    let mut pipe = redis::pipe();
+    pipe.atomic().del(key::sessions()).ignore();
+    for session in sessions {
+        pipe.hset(
+            key::sessions(),
+            session.guild_id,
+            format!("{}:{}", session.voice_channel_id, session.text_channel_id),
+        )
+        .ignore();
+    }
+
+    let () = pipe.query_async(connection).await?;
+    Ok(())
+}
+
+/// 保存されているボイスチャンネルを返し、削除する
+pub async fn take(connection: &mut ConnectionManager) -> Result<Vec<Session>> {
+    let (entries, ()): (Vec<(u64, String)>, ()) = redis::pipe()
+        .atomic()
+        .hgetall(key::sessions())
+        .del(key::sessions())
+        .ignore()
+        .query_async(connection)
+        .await?;
+
+    entries
+        .into_iter()
+        .map(|(guild_id, value)| {
+            let (voice_channel_id, text_channel_id) = value
+                .split_once(':')
+                .with_context(|| format!("Invalid session of guild {guild_id}: {value}"))?;
+            Ok(Session {
+                guild_id,
+                voice_channel_id: voice_channel_id.parse()?,
+                text_channel_id: text_channel_id.parse()?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use anyhow::{Context as _, Result};
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::key;
+use crate::db::setting::{FailureFeedback, GetOption, GuildSetting, ReadFilter, SetOption};
+
+const CATCHUP_SPEEDUP_FIELD: &str = "catchup_speedup";
+const CATCHUP_THRESHOLD_FIELD: &str = "catchup_threshold";
+const VC_MEMBERS_ONLY_FIELD: &str = "vc_members_only";
+/// カンマ区切りで保存する
+const READ_FILTERS_FIELD: &str = "read_filters";
+const FAILURE_FEEDBACK_FIELD: &str = "failure_feedback";
+
+/// サーバーの設定を返す
+/// 未設定の項目はデフォルト値を返す
+pub async fn get(connection: &mut ConnectionManager, option: GetOption) -> Result<GuildSetting> {
+    let resp: HashMap<String, String> = connection
+        .hgetall(key::guild_setting(option.guild_id))
+        .await?;
+
+    let mut setting = GuildSetting::default();
+    if let Some(value) = resp.get(CATCHUP_SPEEDUP_FIELD) {
+        setting.catchup_speedup = value
+            .parse()
+            .with_context(|| format!("Invalid {CATCHUP_SPEEDUP_FIELD} value: {value}"))?;
+    }
+    if let Some(value) = resp.get(CATCHUP_THRESHOLD_FIELD) {
+        setting.catchup_threshold = value
+            .parse()
+            .with_context(|| format!("Invalid {CATCHUP_THRESHOLD_FIELD} value: {value}"))?;
+    }
+    if let Some(value) = resp.get(VC_MEMBERS_ONLY_FIELD) {
+        setting.vc_members_only = value
+            .parse()
+            .with_context(|| format!("Invalid {VC_MEMBERS_ONLY_FIELD} value: {value}"))?;
+    }
+    if let Some(value) = resp.get(READ_FILTERS_FIELD) {
+        setting.read_filters = ReadFilter::split(value)
+            .with_context(|| format!("Invalid {READ_FILTERS_FIELD} value: {value}"))?;
+    }
+    if let Some(value) = resp.get(FAILURE_FEEDBACK_FIELD) {
+        setting.failure_feedback = value
+            .parse::<FailureFeedback>()
+            .with_context(|| format!("Invalid {FAILURE_FEEDBACK_FIELD} value: {value}"))?;
+    }
+
+    Ok(setting)
+}
+
+/// サーバーの設定を保存する
+pub async fn set(connection: &mut ConnectionManager, option: SetOption) -> Result<()> {
+    let fields = [
+        (
+            CATCHUP_SPEEDUP_FIELD,
+            option.setting.catchup_speedup.to_string(),
+        ),
+        (
+            CATCHUP_THRESHOLD_FIELD,
+            option.setting.catchup_threshold.to_string(),
+        ),
+        (
+            VC_MEMBERS_ONLY_FIELD,
+            option.setting.vc_members_only.to_string(),
+        ),
+        (
+            READ_FILTERS_FIELD,
+            ReadFilter::join(&option.setting.read_filters),
+        ),
+        (
+            FAILURE_FEEDBACK_FIELD,
+            option.setting.failure_feedback.to_string(),
+        ),
+    ];
+
+    let () = connection
+        .hset_multiple(key::guild_setting(option.guild_id), &fields)
+        .await?;
+
+    Ok(())
+}
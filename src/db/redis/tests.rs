@@ -0,0 +1,554 @@
+//! Redisを使うテスト
+//!
+//! 環境変数`KOE_TEST_REDIS_URL`で指定されたRedisを使用する。
+//! Redisが必要なため`#[ignore]`を付けており、`cargo test -- --include-ignored`で実行する。
+//! `KOE_TEST_REDIS_URL`が設定されていない場合はスキップする。
+//! テストごとにランダムなIDを使うため、既存のデータとは衝突しない。
+
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::{command, dict, export, ignore, key, migration, name, session, setting, user, voice};
+use crate::{
+    config::RedisConfig,
+    db::{
+        dict::{
+            DictScope, GetAllOption, InsertOption, InsertResponse, RemoveOption, RemoveResponse,
+            UpdateOption, UpdateResponse,
+        },
+        ignore::IgnoreTarget,
+        session::Session,
+        setting::{FailureFeedback, GuildSetting, ReadFilter},
+    },
+};
+
+/// テスト用のRedisに接続する
+///
+/// `KOE_TEST_REDIS_URL`が設定されていない場合は`None`を返し、テストをスキップする。
+/// ただしCIではRedisを用意しているため、設定されていなければ失敗させる。
+async fn connect() -> Option<ConnectionManager> {
+    let Ok(url) = std::env::var("KOE_TEST_REDIS_URL") else {
+        assert!(
+            std::env::var_os("CI").is_none(),
+            "KOE_TEST_REDIS_URL must be set to run tests that require Redis in CI"
+        );
+        eprintln!("Skipping test because KOE_TEST_REDIS_URL is not set");
+        return None;
+    };
+
+    let config = RedisConfig {
+        url,
+        connection_timeout_ms: 1000,
+        response_timeout_ms: 1000,
+        max_retries: 0,
+    };
+    let conn = super::connect(&config)
+        .await
+        .expect("Failed to connect to KOE_TEST_REDIS_URL");
+    Some(conn)
+}
+
+fn random_id() -> u64 {
+    rand::random_range(1..=i64::MAX as u64)
+}
+
+/// テストで作成したサーバーとユーザーのデータを削除する
+async fn cleanup(conn: &mut ConnectionManager, guild_id: u64, user_id: u64) {
+    let keys = {
+        let mut iter = conn
+            .scan_match::<_, String>(format!("guild:{guild_id}:*"))
+            .await
+            .unwrap();
+
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key.unwrap());
+        }
+        keys
+    };
+
+    let () = conn.del(key::user_opt_out(user_id)).await.unwrap();
+    if !keys.is_empty() {
+        let () = conn.del(keys).await.unwrap();
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn dict_words_are_inserted_updated_and_removed() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, user_id) = (random_id(), random_id());
+    let guild_scope = DictScope::Guild { guild_id };
+    let user_scope = DictScope::User { guild_id, user_id };
+
+    let resp = dict::insert(
+        &mut conn,
+        InsertOption {
+            scope: guild_scope,
+            word: "koe".to_string(),
+            read_as: "こえ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, InsertResponse::Success));
+
+    let resp = dict::insert(
+        &mut conn,
+        InsertOption {
+            scope: guild_scope,
+            word: "koe".to_string(),
+            read_as: "コエ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, InsertResponse::WordAlreadyExists));
+
+    let resp = dict::update(
+        &mut conn,
+        UpdateOption {
+            scope: guild_scope,
+            word: "koe".to_string(),
+            read_as: "コエ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, UpdateResponse::Success));
+
+    let resp = dict::update(
+        &mut conn,
+        UpdateOption {
+            scope: user_scope,
+            word: "koe".to_string(),
+            read_as: "こえ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, UpdateResponse::WordDoesNotExist));
+
+    let guild_dict = dict::get_all(&mut conn, GetAllOption { scope: guild_scope })
+        .await
+        .unwrap();
+    assert_eq!(guild_dict, vec![("koe".to_string(), "コエ".to_string())]);
+
+    let user_dict = dict::get_all(&mut conn, GetAllOption { scope: user_scope })
+        .await
+        .unwrap();
+    assert!(user_dict.is_empty());
+
+    let resp = dict::remove(
+        &mut conn,
+        RemoveOption {
+            scope: guild_scope,
+            word: "koe".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, RemoveResponse::Success));
+
+    let resp = dict::remove(
+        &mut conn,
+        RemoveOption {
+            scope: guild_scope,
+            word: "koe".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, RemoveResponse::WordDoesNotExist));
+
+    cleanup(&mut conn, guild_id, user_id).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn voice_falls_back_once_and_can_be_set() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, user_id) = (random_id(), random_id());
+
+    let get = |fallback| crate::db::voice::GetOption {
+        guild_id,
+        user_id,
+        fallback,
+    };
+
+    assert_eq!(voice::get(&mut conn, get(3)).await.unwrap(), 3);
+    // 一度返したフォールバックの値は保存される
+    assert_eq!(voice::get(&mut conn, get(5)).await.unwrap(), 3);
+
+    voice::set(
+        &mut conn,
+        crate::db::voice::SetOption {
+            guild_id,
+            user_id,
+            value: 7,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(voice::get(&mut conn, get(5)).await.unwrap(), 7);
+
+    cleanup(&mut conn, guild_id, user_id).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn setting_defaults_and_round_trips() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let guild_id = random_id();
+
+    let setting = setting::get(&mut conn, crate::db::setting::GetOption { guild_id })
+        .await
+        .unwrap();
+    assert_eq!(setting, GuildSetting::default());
+
+    let new_setting = GuildSetting {
+        catchup_speedup: true,
+        catchup_threshold: 3,
+        vc_members_only: true,
+        read_filters: vec![ReadFilter::Dictionary, ReadFilter::Url],
+        failure_feedback: FailureFeedback::Notice,
+    };
+    setting::set(
+        &mut conn,
+        crate::db::setting::SetOption {
+            guild_id,
+            setting: new_setting.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let setting = setting::get(&mut conn, crate::db::setting::GetOption { guild_id })
+        .await
+        .unwrap();
+    assert_eq!(setting, new_setting);
+
+    cleanup(&mut conn, guild_id, 0).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn ignore_targets_are_inserted_and_removed() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, user_id, role_id) = (random_id(), random_id(), random_id());
+
+    for target in [IgnoreTarget::User(user_id), IgnoreTarget::Role(role_id)] {
+        let resp = ignore::insert(
+            &mut conn,
+            crate::db::ignore::InsertOption { guild_id, target },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(resp, crate::db::ignore::InsertResponse::Success));
+    }
+
+    let resp = ignore::insert(
+        &mut conn,
+        crate::db::ignore::InsertOption {
+            guild_id,
+            target: IgnoreTarget::User(user_id),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::ignore::InsertResponse::AlreadyIgnored
+    ));
+
+    let targets = ignore::get_all(&mut conn, crate::db::ignore::GetAllOption { guild_id })
+        .await
+        .unwrap();
+    assert_eq!(
+        targets,
+        vec![IgnoreTarget::User(user_id), IgnoreTarget::Role(role_id)]
+    );
+
+    let resp = ignore::remove(
+        &mut conn,
+        crate::db::ignore::RemoveOption {
+            guild_id,
+            target: IgnoreTarget::Role(role_id),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(resp, crate::db::ignore::RemoveResponse::Success));
+
+    let resp = ignore::remove(
+        &mut conn,
+        crate::db::ignore::RemoveOption {
+            guild_id,
+            target: IgnoreTarget::Role(role_id),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::ignore::RemoveResponse::NotIgnored
+    ));
+
+    cleanup(&mut conn, guild_id, user_id).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn names_are_set_and_removed() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, user_id, other_user_id) = (random_id(), random_id(), random_id());
+
+    name::set(
+        &mut conn,
+        crate::db::name::SetOption {
+            guild_id,
+            user_id,
+            reading: "こえ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let names = name::get(
+        &mut conn,
+        crate::db::name::GetOption {
+            guild_id,
+            user_ids: vec![user_id, other_user_id],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(names, vec![Some("こえ".to_string()), None]);
+
+    let names = name::get(
+        &mut conn,
+        crate::db::name::GetOption {
+            guild_id,
+            user_ids: Vec::new(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(names.is_empty());
+
+    let remove = || crate::db::name::RemoveOption { guild_id, user_id };
+    let resp = name::remove(&mut conn, remove()).await.unwrap();
+    assert!(matches!(resp, crate::db::name::RemoveResponse::Success));
+    let resp = name::remove(&mut conn, remove()).await.unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::name::RemoveResponse::ReadingDoesNotExist
+    ));
+
+    cleanup(&mut conn, guild_id, user_id).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn user_data_is_deleted_across_guilds_except_opt_out() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, other_guild_id, user_id) = (random_id(), random_id(), random_id());
+
+    for guild_id in [guild_id, other_guild_id] {
+        name::set(
+            &mut conn,
+            crate::db::name::SetOption {
+                guild_id,
+                user_id,
+                reading: "こえ".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    }
+    voice::set(
+        &mut conn,
+        crate::db::voice::SetOption {
+            guild_id,
+            user_id,
+            value: 1,
+        },
+    )
+    .await
+    .unwrap();
+    user::set_opt_out(
+        &mut conn,
+        crate::db::user::SetOptOutOption {
+            user_id,
+            value: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    let count = user::delete_guild_data(
+        &mut conn,
+        crate::db::user::DeleteGuildDataOption { user_id },
+    )
+    .await
+    .unwrap();
+    assert_eq!(count, 3);
+
+    let opted_out = user::get_opt_out(&mut conn, crate::db::user::GetOptOutOption { user_id })
+        .await
+        .unwrap();
+    assert!(opted_out);
+
+    user::set_opt_out(
+        &mut conn,
+        crate::db::user::SetOptOutOption {
+            user_id,
+            value: false,
+        },
+    )
+    .await
+    .unwrap();
+    let opted_out = user::get_opt_out(&mut conn, crate::db::user::GetOptOutOption { user_id })
+        .await
+        .unwrap();
+    assert!(!opted_out);
+
+    cleanup(&mut conn, guild_id, user_id).await;
+    cleanup(&mut conn, other_guild_id, user_id).await;
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn migration_brings_schema_to_latest_version() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    migration::run(&mut conn, migration::RunOption { dry_run: false })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        migration::get_version(&mut conn).await.unwrap(),
+        migration::LATEST_VERSION
+    );
+    let pending = migration::run(&mut conn, migration::RunOption { dry_run: true })
+        .await
+        .unwrap();
+    assert!(pending.is_empty());
+}
+
+/// 終了時の記録はサーバーごとではなく1つのキーにまとめて保存されるため、既存の記録を置き換える
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn sessions_are_saved_and_taken_once() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let sessions = vec![
+        Session {
+            guild_id: random_id(),
+            voice_channel_id: random_id(),
+            text_channel_id: random_id(),
+        },
+        Session {
+            guild_id: random_id(),
+            voice_channel_id: random_id(),
+            text_channel_id: random_id(),
+        },
+    ];
+
+    session::save(&mut conn, sessions.clone()).await.unwrap();
+    session::save(&mut conn, vec![sessions[1].clone()])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        session::take(&mut conn).await.unwrap(),
+        vec![sessions[1].clone()]
+    );
+    assert!(session::take(&mut conn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn command_hash_is_saved_and_removed() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    command::set_hash(&mut conn, Some("0123456789abcdef".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        command::get_hash(&mut conn).await.unwrap().as_deref(),
+        Some("0123456789abcdef")
+    );
+
+    command::set_hash(&mut conn, None).await.unwrap();
+    assert_eq!(command::get_hash(&mut conn).await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "requires Redis"]
+async fn export_includes_stored_data() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let (guild_id, user_id) = (random_id(), random_id());
+
+    dict::insert(
+        &mut conn,
+        InsertOption {
+            scope: DictScope::User { guild_id, user_id },
+            word: "koe".to_string(),
+            read_as: "こえ".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    ignore::insert(
+        &mut conn,
+        crate::db::ignore::InsertOption {
+            guild_id,
+            target: IgnoreTarget::Role(user_id),
+        },
+    )
+    .await
+    .unwrap();
+    user::set_opt_out(
+        &mut conn,
+        crate::db::user::SetOptOutOption {
+            user_id,
+            value: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    let dump = export::export(&mut conn).await.unwrap();
+
+    assert!(dump.dicts.iter().any(|dict| {
+        dict.scope == DictScope::User { guild_id, user_id }
+            && dict.entries == vec![("koe".to_string(), "こえ".to_string())]
+    }));
+    assert!(
+        dump.ignore_targets
+            .iter()
+            .any(|t| t.guild_id == guild_id && t.target == IgnoreTarget::Role(user_id))
+    );
+    assert!(dump.opted_out_users.contains(&user_id));
+
+    cleanup(&mut conn, guild_id, user_id).await;
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use super::key;
+use crate::db::user::{DeleteGuildDataOption, GetOptOutOption, SetOptOutOption};
+
+/// ユーザーがメッセージの読み上げを拒否しているかを返す
+pub async fn get_opt_out(
+    connection: &mut ConnectionManager,
+    option: GetOptOutOption,
+) -> Result<bool> {
+    let resp = connection.exists(key::user_opt_out(option.user_id)).await?;
+    Ok(resp)
+}
+
+/// ユーザーがメッセージの読み上げを拒否しているかを設定する
+pub async fn set_opt_out(
+    connection: &mut ConnectionManager,
+    option: SetOptOutOption,
+) -> Result<()> {
+    let key = key::user_opt_out(option.user_id);
+
+    if option.value {
+        let () = connection.set(key, 1).await?;
+    } else {
+        let () = connection.del(key).await?;
+    }
+
+    Ok(())
+}
+
+/// すべてのサーバーに保存されているユーザーのデータを削除する
+/// 削除したキーの数を返す
+pub async fn delete_guild_data(
+    connection: &mut ConnectionManager,
+    option: DeleteGuildDataOption,
+) -> Result<usize> {
+    let keys = {
+        let mut iter = connection
+            .scan_match::<_, String>(key::guild_user_data_pattern(option.user_id))
+            .await?;
+
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key?);
+        }
+        keys
+    };
+
+    if keys.is_empty() {
+        return Ok(0);
+    }
+
+    let resp = connection.del(keys).await?;
+    Ok(resp)
+}
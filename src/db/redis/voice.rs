@@ -0,0 +1,27 @@
+use anyhow::Result;
+use redis::{AsyncTypedCommands, aio::ConnectionManager};
+
+use super::key;
+use crate::db::voice::{GetOption, SetOption};
+
+/// ユーザーの声を返す
+/// 未設定の場合は`option.fallback`の値を設定して返す
+pub async fn get(connection: &mut ConnectionManager, option: GetOption) -> Result<i64> {
+    let key = key::guild_user_voice(option.guild_id, option.user_id);
+
+    let (resp,) = redis::pipe()
+        .set_nx(&key, option.fallback)
+        .ignore()
+        .get(&key)
+        .query_async(connection)
+        .await?;
+
+    Ok(resp)
+}
+
+/// ユーザーの声を設定する
+pub async fn set(connection: &mut ConnectionManager, option: SetOption) -> Result<()> {
+    let key = key::guild_user_voice(option.guild_id, option.user_id);
+    connection.set(&key, option.value).await?;
+    Ok(())
+}
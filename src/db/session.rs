@@ -0,0 +1,13 @@
+//! 終了時にボイスチャンネルに接続していたサーバーの記録
+//!
+//! Koeを再起動したときに、同じボイスチャンネルに接続し直すために使う。
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_field_names)]
+pub struct Session {
+    pub guild_id: u64,
+    /// 接続していたボイスチャンネル
+    pub voice_channel_id: u64,
+    /// 読み上げていたテキストチャンネル
+    pub text_channel_id: u64,
+}
@@ -0,0 +1,167 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Error, bail};
+
+/// サーバーごとの設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildSetting {
+    /// 読み上げ待ちのメッセージの数に応じて読み上げを速くするか
+    pub catchup_speedup: bool,
+    /// 読み上げ待ちのメッセージがこの数に達したら古いものを省略する
+    /// 0のときは省略しない
+    pub catchup_threshold: u32,
+    /// Koeと同じボイスチャンネルにいるメンバーのメッセージのみを読み上げるか
+    pub vc_members_only: bool,
+    /// 読み上げる文章を組み立てるときに適用する処理。この順に適用する
+    pub read_filters: Vec<ReadFilter>,
+    /// メッセージを読み上げられなかったときのメンバーへの知らせ方
+    pub failure_feedback: FailureFeedback,
+}
+
+impl Default for GuildSetting {
+    fn default() -> Self {
+        Self {
+            catchup_speedup: false,
+            catchup_threshold: 0,
+            vc_members_only: false,
+            read_filters: ReadFilter::DEFAULT_ORDER.to_vec(),
+            failure_feedback: FailureFeedback::default(),
+        }
+    }
+}
+
+/// 読み上げる文章を組み立てるときに適用する処理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReadFilter {
+    /// ID表記されたメンションを名前に置き換える
+    Mention,
+    /// カスタム絵文字を名前に置き換える
+    CustomEmoji,
+    /// Markdownの書式とスポイラーを取り除く
+    Markdown,
+    /// URLを取り除く
+    Url,
+    /// 送信者の名前を先頭に付ける
+    AuthorName,
+    /// 辞書に登録されている語句を読み替える
+    Dictionary,
+    /// 長い文章を省略する
+    Truncate,
+}
+
+impl ReadFilter {
+    pub const DEFAULT_ORDER: [Self; 7] = [
+        Self::Mention,
+        Self::CustomEmoji,
+        Self::Markdown,
+        Self::Url,
+        Self::AuthorName,
+        Self::Dictionary,
+        Self::Truncate,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Mention => "mention",
+            Self::CustomEmoji => "custom_emoji",
+            Self::Markdown => "markdown",
+            Self::Url => "url",
+            Self::AuthorName => "author_name",
+            Self::Dictionary => "dictionary",
+            Self::Truncate => "truncate",
+        }
+    }
+
+    /// カンマ区切りの文字列に変換する
+    pub fn join(filters: &[Self]) -> String {
+        filters
+            .iter()
+            .map(|filter| filter.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// カンマ区切りの文字列を変換する
+    pub fn split(s: &str) -> Result<Vec<Self>, Error> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for ReadFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReadFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::DEFAULT_ORDER
+            .into_iter()
+            .find(|filter| filter.as_str() == s)
+        {
+            Some(filter) => Ok(filter),
+            None => bail!("Unknown read filter: {s}"),
+        }
+    }
+}
+
+/// メッセージを読み上げられなかったときのメンバーへの知らせ方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailureFeedback {
+    /// 何もしない
+    Off,
+    /// 読み上げられなかったメッセージにリアクションを付ける
+    #[default]
+    Reaction,
+    /// 読み上げられなかった理由を読み上げ対象のチャンネルに送信する
+    Notice,
+}
+
+impl FailureFeedback {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Reaction, Self::Notice];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Reaction => "reaction",
+            Self::Notice => "notice",
+        }
+    }
+}
+
+impl fmt::Display for FailureFeedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FailureFeedback {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL
+            .into_iter()
+            .find(|feedback| feedback.as_str() == s)
+        {
+            Some(feedback) => Ok(feedback),
+            None => bail!("Unknown failure feedback: {s}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetOption {
+    pub guild_id: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetOption {
+    pub guild_id: u64,
+    pub setting: GuildSetting,
+}
@@ -0,0 +1,677 @@
+//! [`Storage`]の`SQLite`による実装
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{
+    Storage,
+    dict::{self, DictScope},
+    dump::{DictDump, Dump, IgnoreTargetDump, NameDump, SettingDump, VoiceDump},
+    ignore::{self, IgnoreTarget},
+    name,
+    session::Session,
+    setting::{self, FailureFeedback, GuildSetting, ReadFilter},
+    user, voice,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// 現在のスキーマのバージョン
+/// リリース後にスキーマを変更する場合は、バージョンを上げて古いバージョンからの移行を追加すること
+const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = "
+    -- サーバーの辞書はuser_idを0とする
+    CREATE TABLE dict (
+        guild_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        word TEXT NOT NULL,
+        read_as TEXT NOT NULL,
+        PRIMARY KEY (guild_id, user_id, word)
+    );
+    CREATE TABLE voice (
+        guild_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        preset_id INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, user_id)
+    );
+    CREATE TABLE setting (
+        guild_id INTEGER PRIMARY KEY,
+        catchup_speedup INTEGER NOT NULL,
+        catchup_threshold INTEGER NOT NULL,
+        vc_members_only INTEGER NOT NULL,
+        -- カンマ区切り。NULLのときはデフォルトの順序とする
+        read_filters TEXT,
+        -- NULLのときはデフォルトとする
+        failure_feedback TEXT
+    );
+    -- kindは'user'または'role'
+    CREATE TABLE ignore_target (
+        guild_id INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        target_id INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, kind, target_id)
+    );
+    CREATE TABLE name (
+        guild_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        reading TEXT NOT NULL,
+        PRIMARY KEY (guild_id, user_id)
+    );
+    CREATE TABLE opted_out_user (
+        user_id INTEGER PRIMARY KEY
+    );
+    CREATE TABLE session (
+        guild_id INTEGER PRIMARY KEY,
+        voice_channel_id INTEGER NOT NULL,
+        text_channel_id INTEGER NOT NULL
+    );
+    -- 行は常に1つ以下
+    CREATE TABLE command_hash (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        hash TEXT NOT NULL
+    );
+";
+
+pub struct SqliteStorage {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    /// データベースのファイルを開き、必要であればテーブルを作成する
+    pub async fn open(path: &Path) -> Result<Self> {
+        let path = path.to_path_buf();
+        let connection = tokio::task::spawn_blocking(move || -> Result<Connection> {
+            let mut connection = Connection::open(&path)
+                .with_context(|| format!("Failed to open SQLite database at {}", path.display()))?;
+
+            let tx = connection.transaction()?;
+            let version: u32 = tx.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+            match version {
+                0 => tx.execute_batch(SCHEMA)?,
+                SCHEMA_VERSION => {}
+                _ => bail!(
+                    "Database schema version {version} is newer than the latest version supported \
+                     by this build ({SCHEMA_VERSION})"
+                ),
+            }
+            if version != SCHEMA_VERSION {
+                tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            tx.commit()?;
+
+            Ok(connection)
+        })
+        .await??;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// rusqliteはブロッキングするため、別のスレッドで`f`を実行する
+    async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| anyhow!("SQLite connection mutex is poisoned"))?;
+            f(&mut connection)
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn insert_dict_word(&self, option: dict::InsertOption) -> Result<dict::InsertResponse> {
+        self.call(move |conn| {
+            let (guild_id, user_id) = scope_ids(option.scope);
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO dict (guild_id, user_id, word, read_as)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![guild_id, user_id, option.word, option.read_as],
+            )?;
+
+            Ok(match inserted {
+                0 => dict::InsertResponse::WordAlreadyExists,
+                _ => dict::InsertResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn update_dict_word(&self, option: dict::UpdateOption) -> Result<dict::UpdateResponse> {
+        self.call(move |conn| {
+            let (guild_id, user_id) = scope_ids(option.scope);
+            let updated = conn.execute(
+                "UPDATE dict SET read_as = ?4 WHERE guild_id = ?1 AND user_id = ?2 AND word = ?3",
+                params![guild_id, user_id, option.word, option.read_as],
+            )?;
+
+            Ok(match updated {
+                0 => dict::UpdateResponse::WordDoesNotExist,
+                _ => dict::UpdateResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn remove_dict_word(&self, option: dict::RemoveOption) -> Result<dict::RemoveResponse> {
+        self.call(move |conn| {
+            let (guild_id, user_id) = scope_ids(option.scope);
+            let removed = conn.execute(
+                "DELETE FROM dict WHERE guild_id = ?1 AND user_id = ?2 AND word = ?3",
+                params![guild_id, user_id, option.word],
+            )?;
+
+            Ok(match removed {
+                0 => dict::RemoveResponse::WordDoesNotExist,
+                _ => dict::RemoveResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn get_dict(&self, option: dict::GetAllOption) -> Result<Vec<(String, String)>> {
+        self.call(move |conn| {
+            let (guild_id, user_id) = scope_ids(option.scope);
+            let mut stmt = conn
+                .prepare("SELECT word, read_as FROM dict WHERE guild_id = ?1 AND user_id = ?2")?;
+            let dict = stmt
+                .query_map(params![guild_id, user_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+
+            Ok(dict)
+        })
+        .await
+    }
+
+    async fn get_voice(&self, option: voice::GetOption) -> Result<i64> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT OR IGNORE INTO voice (guild_id, user_id, preset_id) VALUES (?1, ?2, ?3)",
+                params![option.guild_id, option.user_id, option.fallback],
+            )?;
+            let voice = tx.query_row(
+                "SELECT preset_id FROM voice WHERE guild_id = ?1 AND user_id = ?2",
+                params![option.guild_id, option.user_id],
+                |row| row.get(0),
+            )?;
+            tx.commit()?;
+
+            Ok(voice)
+        })
+        .await
+    }
+
+    async fn set_voice(&self, option: voice::SetOption) -> Result<()> {
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO voice (guild_id, user_id, preset_id) VALUES (?1, ?2, ?3)",
+                params![option.guild_id, option.user_id, option.value],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_setting(&self, option: setting::GetOption) -> Result<GuildSetting> {
+        self.call(move |conn| {
+            let setting = conn
+                .query_row(
+                    "SELECT catchup_speedup, catchup_threshold, vc_members_only, read_filters,
+                     failure_feedback
+                     FROM setting WHERE guild_id = ?1",
+                    params![option.guild_id],
+                    |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                        ))
+                    },
+                )
+                .optional()?;
+
+            setting
+                .map(parse_setting)
+                .transpose()
+                .map(Option::unwrap_or_default)
+        })
+        .await
+    }
+
+    async fn set_setting(&self, option: setting::SetOption) -> Result<()> {
+        self.call(move |conn| {
+            let setting = option.setting;
+            conn.execute(
+                "INSERT OR REPLACE INTO setting
+                 (guild_id, catchup_speedup, catchup_threshold, vc_members_only, read_filters,
+                  failure_feedback)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    option.guild_id,
+                    setting.catchup_speedup,
+                    setting.catchup_threshold,
+                    setting.vc_members_only,
+                    ReadFilter::join(&setting.read_filters),
+                    setting.failure_feedback.as_str()
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn insert_ignore_target(
+        &self,
+        option: ignore::InsertOption,
+    ) -> Result<ignore::InsertResponse> {
+        self.call(move |conn| {
+            let (kind, target_id) = target_kind_and_id(option.target);
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO ignore_target (guild_id, kind, target_id)
+                 VALUES (?1, ?2, ?3)",
+                params![option.guild_id, kind, target_id],
+            )?;
+
+            Ok(match inserted {
+                0 => ignore::InsertResponse::AlreadyIgnored,
+                _ => ignore::InsertResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn remove_ignore_target(
+        &self,
+        option: ignore::RemoveOption,
+    ) -> Result<ignore::RemoveResponse> {
+        self.call(move |conn| {
+            let (kind, target_id) = target_kind_and_id(option.target);
+            let removed = conn.execute(
+                "DELETE FROM ignore_target WHERE guild_id = ?1 AND kind = ?2 AND target_id = ?3",
+                params![option.guild_id, kind, target_id],
+            )?;
+
+            Ok(match removed {
+                0 => ignore::RemoveResponse::NotIgnored,
+                _ => ignore::RemoveResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn get_ignore_targets(&self, option: ignore::GetAllOption) -> Result<Vec<IgnoreTarget>> {
+        self.call(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT kind, target_id FROM ignore_target WHERE guild_id = ?1")?;
+            let rows = stmt
+                .query_map(params![option.guild_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(kind, target_id)| parse_target(&kind, target_id))
+                .collect()
+        })
+        .await
+    }
+
+    async fn get_names(&self, option: name::GetOption) -> Result<Vec<Option<String>>> {
+        self.call(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT reading FROM name WHERE guild_id = ?1 AND user_id = ?2")?;
+            let names = option
+                .user_ids
+                .iter()
+                .map(|user_id| {
+                    stmt.query_row(params![option.guild_id, user_id], |row| row.get(0))
+                        .optional()
+                })
+                .collect::<rusqlite::Result<_>>()?;
+
+            Ok(names)
+        })
+        .await
+    }
+
+    async fn set_name(&self, option: name::SetOption) -> Result<()> {
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO name (guild_id, user_id, reading) VALUES (?1, ?2, ?3)",
+                params![option.guild_id, option.user_id, option.reading],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove_name(&self, option: name::RemoveOption) -> Result<name::RemoveResponse> {
+        self.call(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM name WHERE guild_id = ?1 AND user_id = ?2",
+                params![option.guild_id, option.user_id],
+            )?;
+
+            Ok(match removed {
+                0 => name::RemoveResponse::ReadingDoesNotExist,
+                _ => name::RemoveResponse::Success,
+            })
+        })
+        .await
+    }
+
+    async fn get_opt_out(&self, option: user::GetOptOutOption) -> Result<bool> {
+        self.call(move |conn| {
+            let opted_out = conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM opted_out_user WHERE user_id = ?1)",
+                params![option.user_id],
+                |row| row.get(0),
+            )?;
+            Ok(opted_out)
+        })
+        .await
+    }
+
+    async fn set_opt_out(&self, option: user::SetOptOutOption) -> Result<()> {
+        self.call(move |conn| {
+            if option.value {
+                conn.execute(
+                    "INSERT OR IGNORE INTO opted_out_user (user_id) VALUES (?1)",
+                    params![option.user_id],
+                )?;
+            } else {
+                conn.execute(
+                    "DELETE FROM opted_out_user WHERE user_id = ?1",
+                    params![option.user_id],
+                )?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_guild_user_data(&self, option: user::DeleteGuildDataOption) -> Result<usize> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            // Redisと同様に、辞書はサーバーごとに1件として数える
+            let dict_count: usize = tx.query_row(
+                "SELECT COUNT(DISTINCT guild_id) FROM dict WHERE user_id = ?1",
+                params![option.user_id],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "DELETE FROM dict WHERE user_id = ?1",
+                params![option.user_id],
+            )?;
+            let voice_count = tx.execute(
+                "DELETE FROM voice WHERE user_id = ?1",
+                params![option.user_id],
+            )?;
+            let name_count = tx.execute(
+                "DELETE FROM name WHERE user_id = ?1",
+                params![option.user_id],
+            )?;
+
+            tx.commit()?;
+            Ok(dict_count + voice_count + name_count)
+        })
+        .await
+    }
+
+    async fn export(&self) -> Result<Dump> {
+        self.call(|conn| {
+            let mut dump = Dump::default();
+
+            let mut stmt = conn.prepare(
+                "SELECT guild_id, user_id, word, read_as FROM dict ORDER BY guild_id, user_id",
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let scope = match row.get(1)? {
+                    0 => DictScope::Guild {
+                        guild_id: row.get(0)?,
+                    },
+                    user_id => DictScope::User {
+                        guild_id: row.get(0)?,
+                        user_id,
+                    },
+                };
+                let entry = (row.get(2)?, row.get(3)?);
+
+                match dump.dicts.last_mut() {
+                    Some(dict) if dict.scope == scope => dict.entries.push(entry),
+                    _ => dump.dicts.push(DictDump {
+                        scope,
+                        entries: vec![entry],
+                    }),
+                }
+            }
+
+            let mut stmt = conn.prepare("SELECT guild_id, user_id, preset_id FROM voice")?;
+            dump.voices = stmt
+                .query_map([], |row| {
+                    Ok(VoiceDump {
+                        guild_id: row.get(0)?,
+                        user_id: row.get(1)?,
+                        preset_id: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut stmt = conn.prepare(
+                "SELECT guild_id, catchup_speedup, catchup_threshold, vc_members_only, \
+                 read_filters, failure_feedback
+                 FROM setting",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        (
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                        ),
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            dump.settings = rows
+                .into_iter()
+                .map(|(guild_id, row)| {
+                    Ok(SettingDump {
+                        guild_id,
+                        setting: parse_setting(row)?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            let mut stmt = conn.prepare("SELECT guild_id, kind, target_id FROM ignore_target")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get::<_, String>(1)?, row.get(2)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            dump.ignore_targets = rows
+                .into_iter()
+                .map(|(guild_id, kind, target_id)| {
+                    Ok(IgnoreTargetDump {
+                        guild_id,
+                        target: parse_target(&kind, target_id)?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            let mut stmt = conn.prepare("SELECT guild_id, user_id, reading FROM name")?;
+            dump.names = stmt
+                .query_map([], |row| {
+                    Ok(NameDump {
+                        guild_id: row.get(0)?,
+                        user_id: row.get(1)?,
+                        reading: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut stmt = conn.prepare("SELECT user_id FROM opted_out_user")?;
+            dump.opted_out_users = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            Ok(dump)
+        })
+        .await
+    }
+
+    async fn save_sessions(&self, sessions: Vec<Session>) -> Result<()> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM session", [])?;
+            for session in sessions {
+                tx.execute(
+                    "INSERT INTO session (guild_id, voice_channel_id, text_channel_id)
+                     VALUES (?1, ?2, ?3)",
+                    params![
+                        session.guild_id,
+                        session.voice_channel_id,
+                        session.text_channel_id
+                    ],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn take_sessions(&self) -> Result<Vec<Session>> {
+        self.call(|conn| {
+            let tx = conn.transaction()?;
+            let sessions = tx
+                .prepare("SELECT guild_id, voice_channel_id, text_channel_id FROM session")?
+                .query_map([], |row| {
+                    Ok(Session {
+                        guild_id: row.get(0)?,
+                        voice_channel_id: row.get(1)?,
+                        text_channel_id: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            tx.execute("DELETE FROM session", [])?;
+            tx.commit()?;
+            Ok(sessions)
+        })
+        .await
+    }
+
+    async fn get_command_hash(&self) -> Result<Option<String>> {
+        self.call(|conn| {
+            let hash = conn
+                .query_row("SELECT hash FROM command_hash WHERE id = 0", [], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            Ok(hash)
+        })
+        .await
+    }
+
+    async fn set_command_hash(&self, hash: Option<String>) -> Result<()> {
+        self.call(move |conn| {
+            match hash {
+                Some(hash) => conn.execute(
+                    "INSERT OR REPLACE INTO command_hash (id, hash) VALUES (0, ?1)",
+                    params![hash],
+                )?,
+                None => conn.execute("DELETE FROM command_hash", [])?,
+            };
+            Ok(())
+        })
+        .await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.call(|conn| {
+            conn.query_row("SELECT 1", [], |_| Ok(()))?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// 辞書の種類に対応する(サーバーのID, ユーザーのID)を返す
+fn scope_ids(scope: DictScope) -> (u64, u64) {
+    match scope {
+        DictScope::Guild { guild_id } => (guild_id, 0),
+        DictScope::User { guild_id, user_id } => (guild_id, user_id),
+    }
+}
+
+fn target_kind_and_id(target: IgnoreTarget) -> (&'static str, u64) {
+    match target {
+        IgnoreTarget::User(user_id) => ("user", user_id),
+        IgnoreTarget::Role(role_id) => ("role", role_id),
+    }
+}
+
+fn parse_target(kind: &str, target_id: u64) -> Result<IgnoreTarget> {
+    match kind {
+        "user" => Ok(IgnoreTarget::User(target_id)),
+        "role" => Ok(IgnoreTarget::Role(target_id)),
+        _ => bail!("Unknown ignore target kind: {kind}"),
+    }
+}
+
+/// `setting`テーブルの行を変換する
+fn parse_setting(
+    (catchup_speedup, catchup_threshold, vc_members_only, read_filters, failure_feedback): (
+        bool,
+        u32,
+        bool,
+        Option<String>,
+        Option<String>,
+    ),
+) -> Result<GuildSetting> {
+    let read_filters = match read_filters {
+        Some(read_filters) => ReadFilter::split(&read_filters)
+            .with_context(|| format!("Invalid read_filters value: {read_filters}"))?,
+        None => ReadFilter::DEFAULT_ORDER.to_vec(),
+    };
+    let failure_feedback = match failure_feedback {
+        Some(failure_feedback) => failure_feedback
+            .parse::<FailureFeedback>()
+            .with_context(|| format!("Invalid failure_feedback value: {failure_feedback}"))?,
+        None => FailureFeedback::default(),
+    };
+
+    Ok(GuildSetting {
+        catchup_speedup,
+        catchup_threshold,
+        vc_members_only,
+        read_filters,
+        failure_feedback,
+    })
+}
@@ -0,0 +1,367 @@
+//! `SQLite`を使うテスト
+//!
+//! テストごとにメモリ上のデータベースを使うため、外部のサービスは必要ない。
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use super::{SCHEMA_VERSION, SqliteStorage};
+use crate::db::{
+    Storage,
+    dict::{
+        DictScope, GetAllOption, InsertOption, InsertResponse, RemoveOption, RemoveResponse,
+        UpdateOption, UpdateResponse,
+    },
+    ignore::IgnoreTarget,
+    session::Session,
+    setting::{FailureFeedback, GuildSetting, ReadFilter},
+};
+
+const GUILD_ID: u64 = 1;
+const OTHER_GUILD_ID: u64 = 2;
+const USER_ID: u64 = 10;
+const OTHER_USER_ID: u64 = 11;
+const ROLE_ID: u64 = 20;
+
+async fn open() -> SqliteStorage {
+    SqliteStorage::open(Path::new(":memory:")).await.unwrap()
+}
+
+/// テスト用の一時ファイルのパスを返す
+fn temp_path() -> PathBuf {
+    std::env::temp_dir().join(format!("koe-test-{}.sqlite", rand::random::<u64>()))
+}
+
+#[tokio::test]
+async fn dict_words_are_inserted_updated_and_removed() {
+    let storage = open().await;
+    let guild_scope = DictScope::Guild { guild_id: GUILD_ID };
+    let user_scope = DictScope::User {
+        guild_id: GUILD_ID,
+        user_id: USER_ID,
+    };
+
+    let insert = |read_as: &str| InsertOption {
+        scope: guild_scope,
+        word: "koe".to_string(),
+        read_as: read_as.to_string(),
+    };
+    let resp = storage.insert_dict_word(insert("こえ")).await.unwrap();
+    assert!(matches!(resp, InsertResponse::Success));
+    let resp = storage.insert_dict_word(insert("コエ")).await.unwrap();
+    assert!(matches!(resp, InsertResponse::WordAlreadyExists));
+
+    let update = |scope| UpdateOption {
+        scope,
+        word: "koe".to_string(),
+        read_as: "コエ".to_string(),
+    };
+    let resp = storage.update_dict_word(update(guild_scope)).await.unwrap();
+    assert!(matches!(resp, UpdateResponse::Success));
+    let resp = storage.update_dict_word(update(user_scope)).await.unwrap();
+    assert!(matches!(resp, UpdateResponse::WordDoesNotExist));
+
+    let guild_dict = storage
+        .get_dict(GetAllOption { scope: guild_scope })
+        .await
+        .unwrap();
+    assert_eq!(guild_dict, vec![("koe".to_string(), "コエ".to_string())]);
+    let user_dict = storage
+        .get_dict(GetAllOption { scope: user_scope })
+        .await
+        .unwrap();
+    assert!(user_dict.is_empty());
+
+    let remove = || RemoveOption {
+        scope: guild_scope,
+        word: "koe".to_string(),
+    };
+    let resp = storage.remove_dict_word(remove()).await.unwrap();
+    assert!(matches!(resp, RemoveResponse::Success));
+    let resp = storage.remove_dict_word(remove()).await.unwrap();
+    assert!(matches!(resp, RemoveResponse::WordDoesNotExist));
+}
+
+#[tokio::test]
+async fn voice_falls_back_once_and_can_be_set() {
+    let storage = open().await;
+
+    let get = |fallback| crate::db::voice::GetOption {
+        guild_id: GUILD_ID,
+        user_id: USER_ID,
+        fallback,
+    };
+
+    assert_eq!(storage.get_voice(get(3)).await.unwrap(), 3);
+    // 一度返したフォールバックの値は保存される
+    assert_eq!(storage.get_voice(get(5)).await.unwrap(), 3);
+
+    storage
+        .set_voice(crate::db::voice::SetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            value: 7,
+        })
+        .await
+        .unwrap();
+    assert_eq!(storage.get_voice(get(5)).await.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn setting_defaults_and_round_trips() {
+    let storage = open().await;
+    let get = || crate::db::setting::GetOption { guild_id: GUILD_ID };
+
+    assert_eq!(
+        storage.get_setting(get()).await.unwrap(),
+        GuildSetting::default()
+    );
+
+    let new_setting = GuildSetting {
+        catchup_speedup: true,
+        catchup_threshold: 3,
+        vc_members_only: true,
+        read_filters: vec![ReadFilter::Dictionary, ReadFilter::Url],
+        failure_feedback: FailureFeedback::Notice,
+    };
+    storage
+        .set_setting(crate::db::setting::SetOption {
+            guild_id: GUILD_ID,
+            setting: new_setting.clone(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(storage.get_setting(get()).await.unwrap(), new_setting);
+}
+
+#[tokio::test]
+async fn ignore_targets_are_inserted_and_removed() {
+    let storage = open().await;
+
+    for target in [IgnoreTarget::User(USER_ID), IgnoreTarget::Role(ROLE_ID)] {
+        let resp = storage
+            .insert_ignore_target(crate::db::ignore::InsertOption {
+                guild_id: GUILD_ID,
+                target,
+            })
+            .await
+            .unwrap();
+        assert!(matches!(resp, crate::db::ignore::InsertResponse::Success));
+    }
+
+    let resp = storage
+        .insert_ignore_target(crate::db::ignore::InsertOption {
+            guild_id: GUILD_ID,
+            target: IgnoreTarget::User(USER_ID),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::ignore::InsertResponse::AlreadyIgnored
+    ));
+
+    let mut targets = storage
+        .get_ignore_targets(crate::db::ignore::GetAllOption { guild_id: GUILD_ID })
+        .await
+        .unwrap();
+    targets.sort_by_key(|target| matches!(target, IgnoreTarget::Role(_)));
+    assert_eq!(
+        targets,
+        vec![IgnoreTarget::User(USER_ID), IgnoreTarget::Role(ROLE_ID)]
+    );
+
+    let remove = || crate::db::ignore::RemoveOption {
+        guild_id: GUILD_ID,
+        target: IgnoreTarget::Role(ROLE_ID),
+    };
+    let resp = storage.remove_ignore_target(remove()).await.unwrap();
+    assert!(matches!(resp, crate::db::ignore::RemoveResponse::Success));
+    let resp = storage.remove_ignore_target(remove()).await.unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::ignore::RemoveResponse::NotIgnored
+    ));
+}
+
+#[tokio::test]
+async fn names_are_set_and_removed() {
+    let storage = open().await;
+
+    storage
+        .set_name(crate::db::name::SetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            reading: "こえ".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let names = storage
+        .get_names(crate::db::name::GetOption {
+            guild_id: GUILD_ID,
+            user_ids: vec![USER_ID, OTHER_USER_ID],
+        })
+        .await
+        .unwrap();
+    assert_eq!(names, vec![Some("こえ".to_string()), None]);
+
+    let names = storage
+        .get_names(crate::db::name::GetOption {
+            guild_id: GUILD_ID,
+            user_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+    assert!(names.is_empty());
+
+    let remove = || crate::db::name::RemoveOption {
+        guild_id: GUILD_ID,
+        user_id: USER_ID,
+    };
+    let resp = storage.remove_name(remove()).await.unwrap();
+    assert!(matches!(resp, crate::db::name::RemoveResponse::Success));
+    let resp = storage.remove_name(remove()).await.unwrap();
+    assert!(matches!(
+        resp,
+        crate::db::name::RemoveResponse::ReadingDoesNotExist
+    ));
+}
+
+#[tokio::test]
+async fn user_data_is_deleted_across_guilds_except_opt_out() {
+    let storage = open().await;
+
+    for guild_id in [GUILD_ID, OTHER_GUILD_ID] {
+        storage
+            .set_name(crate::db::name::SetOption {
+                guild_id,
+                user_id: USER_ID,
+                reading: "こえ".to_string(),
+            })
+            .await
+            .unwrap();
+    }
+    storage
+        .set_voice(crate::db::voice::SetOption {
+            guild_id: GUILD_ID,
+            user_id: USER_ID,
+            value: 1,
+        })
+        .await
+        .unwrap();
+    let set_opt_out = |value| crate::db::user::SetOptOutOption {
+        user_id: USER_ID,
+        value,
+    };
+    storage.set_opt_out(set_opt_out(true)).await.unwrap();
+
+    let count = storage
+        .delete_guild_user_data(crate::db::user::DeleteGuildDataOption { user_id: USER_ID })
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+
+    let get_opt_out = || crate::db::user::GetOptOutOption { user_id: USER_ID };
+    assert!(storage.get_opt_out(get_opt_out()).await.unwrap());
+
+    storage.set_opt_out(set_opt_out(false)).await.unwrap();
+    assert!(!storage.get_opt_out(get_opt_out()).await.unwrap());
+}
+
+#[tokio::test]
+async fn sessions_are_saved_and_taken_once() {
+    let storage = open().await;
+    let sessions = vec![
+        Session {
+            guild_id: GUILD_ID,
+            voice_channel_id: 100,
+            text_channel_id: 101,
+        },
+        Session {
+            guild_id: OTHER_GUILD_ID,
+            voice_channel_id: 200,
+            text_channel_id: 201,
+        },
+    ];
+
+    storage.save_sessions(sessions.clone()).await.unwrap();
+    storage
+        .save_sessions(vec![sessions[1].clone()])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        storage.take_sessions().await.unwrap(),
+        vec![sessions[1].clone()]
+    );
+    assert!(storage.take_sessions().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn command_hash_is_saved_and_removed() {
+    let storage = open().await;
+    assert_eq!(storage.get_command_hash().await.unwrap(), None);
+
+    storage
+        .set_command_hash(Some("0123456789abcdef".to_string()))
+        .await
+        .unwrap();
+    storage
+        .set_command_hash(Some("fedcba9876543210".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        storage.get_command_hash().await.unwrap().as_deref(),
+        Some("fedcba9876543210")
+    );
+
+    storage.set_command_hash(None).await.unwrap();
+    assert_eq!(storage.get_command_hash().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn data_is_kept_after_reopening() {
+    let path = temp_path();
+    {
+        let storage = SqliteStorage::open(&path).await.unwrap();
+        storage
+            .set_command_hash(Some("hash".to_string()))
+            .await
+            .unwrap();
+    }
+
+    let storage = SqliteStorage::open(&path).await.unwrap();
+    assert_eq!(
+        storage.get_command_hash().await.unwrap().as_deref(),
+        Some("hash")
+    );
+    drop(storage);
+
+    let connection = Connection::open(&path).unwrap();
+    let version: u32 = connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, SCHEMA_VERSION);
+    drop(connection);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn newer_schema_is_rejected() {
+    let path = temp_path();
+    {
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+    }
+
+    let result = SqliteStorage::open(&path).await;
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,15 @@
+#[derive(Debug, Clone)]
+pub struct GetOptOutOption {
+    pub user_id: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetOptOutOption {
+    pub user_id: u64,
+    pub value: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteGuildDataOption {
+    pub user_id: u64,
+}
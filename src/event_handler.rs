@@ -1,70 +1,146 @@
 use anyhow::Context as _;
-use log::{error, info};
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
     gateway::ActivityData,
     model::{
-        application::Interaction, channel::Message, gateway::Ready, guild::Guild, voice::VoiceState,
+        application::Interaction,
+        channel::Message,
+        gateway::Ready,
+        guild::Guild,
+        id::{ChannelId, GuildId, UserId},
+        voice::VoiceState,
     },
 };
+use tracing::{Span, error, info, instrument};
 
-use crate::{commands, components, message, voice_state};
+use crate::{
+    app_state,
+    commands::{self, registration},
+    components,
+    config::CommandScope,
+    db::StorageUnavailable,
+    message, metrics, session, voice_state,
+};
 
-pub struct Handler;
+pub struct Handler {
+    pub command_scope: CommandScope,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
+    #[instrument(skip_all, fields(shard_id = ready.shard.map(|shard| shard.id.0)))]
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!("Connected as {}", ready.user.name);
 
         ctx.set_activity(Some(ActivityData::playing("テキストチャット 読み上げBot")));
 
-        for guild in &ready.guilds {
-            if let Err(err) = guild
-                .id
-                .set_commands(&ctx.http, commands::commands())
+        // Every shard receives READY, but the global commands only need to be checked once
+        let is_first_shard = ready.shard.is_none_or(|shard| shard.id.0 == 0);
+        if self.command_scope == CommandScope::Global
+            && is_first_shard
+            && let Err(err) = register_global_commands(&ctx)
                 .await
-                .context("Failed to set guild application commands")
-            {
-                error!("{err:?}");
-            }
+                .context("Failed to register global application commands")
+        {
+            error!("{err:?}");
         }
     }
 
-    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
-        if let Err(err) = guild
-            .id
-            .set_commands(&ctx.http, commands::commands())
+    #[instrument(skip_all)]
+    async fn cache_ready(&self, ctx: Context, _guilds: Vec<GuildId>) {
+        if let Err(err) = session::restore(&ctx)
             .await
-            .context("Failed to set guild application commands")
+            .context("Failed to restore voice sessions")
+        {
+            error!("{err:?}");
+        }
+    }
+
+    #[instrument(level = "debug", skip_all, fields(guild_id = guild.id.get()))]
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
+        if self.command_scope == CommandScope::Guild
+            && let Err(err) = registration::register_guild(&ctx.http, guild.id).await
         {
             error!("{err:?}");
         }
     }
 
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            interaction_id = interaction.id().get(),
+            guild_id = interaction.guild_id().map(GuildId::get),
+            channel_id,
+            user_id,
+        )
+    )]
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         match interaction {
             Interaction::Command(command) => {
+                record_ids(command.channel_id, command.user.id);
+                metrics::command_invoked(&command.data.name);
+
                 if let Err(err) = commands::handle_interaction(&ctx, &command)
                     .await
                     .context("Failed to respond to slash command")
+                {
+                    error!("{err:?}");
+
+                    if err.downcast_ref::<StorageUnavailable>().is_some()
+                        && let Err(err) = commands::respond_storage_unavailable(&ctx, &command)
+                            .await
+                            .context("Failed to notify that storage is unavailable")
+                    {
+                        error!("{err:?}");
+                    }
+                }
+            }
+            Interaction::Autocomplete(command) => {
+                record_ids(command.channel_id, command.user.id);
+                if let Err(err) = commands::handle_autocomplete(&ctx, &command)
+                    .await
+                    .context("Failed to respond to autocomplete interaction")
                 {
                     error!("{err:?}");
                 }
             }
             Interaction::Component(component_interaction) => {
+                record_ids(
+                    component_interaction.channel_id,
+                    component_interaction.user.id,
+                );
                 if let Err(err) = components::handle_interaction(&ctx, &component_interaction)
                     .await
                     .context("Failed to respond to message components interaction")
                 {
                     error!("{err:?}");
+
+                    if err.downcast_ref::<StorageUnavailable>().is_some()
+                        && let Err(err) =
+                            components::respond_storage_unavailable(&ctx, &component_interaction)
+                                .await
+                                .context("Failed to notify that storage is unavailable")
+                    {
+                        error!("{err:?}");
+                    }
                 }
             }
             _ => {}
         }
     }
 
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            guild_id = msg.guild_id.map(GuildId::get),
+            channel_id = msg.channel_id.get(),
+            message_id = msg.id.get(),
+            user_id = msg.author.id.get(),
+        )
+    )]
     async fn message(&self, ctx: Context, msg: Message) {
         if let Err(err) = message::handle(&ctx, msg)
             .await
@@ -74,6 +150,15 @@ impl EventHandler for Handler {
         }
     }
 
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            guild_id = new_voice_state.guild_id.map(GuildId::get),
+            channel_id = new_voice_state.channel_id.map(ChannelId::get),
+            user_id = new_voice_state.user_id.get(),
+        )
+    )]
     async fn voice_state_update(
         &self,
         ctx: Context,
@@ -88,3 +173,16 @@ impl EventHandler for Handler {
         }
     }
 }
+
+/// Records the IDs that are only available after finding the kind of the interaction.
+fn record_ids(channel_id: ChannelId, user_id: UserId) {
+    let span = Span::current();
+    span.record("channel_id", channel_id.get());
+    span.record("user_id", user_id.get());
+}
+
+async fn register_global_commands(ctx: &Context) -> anyhow::Result<()> {
+    let state = app_state::get(ctx).await?;
+    registration::register_global_if_changed(&ctx.http, state.storage.as_ref()).await?;
+    Ok(())
+}
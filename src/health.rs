@@ -0,0 +1,235 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use serde::{Serialize, Serializer};
+use serenity::gateway::{ConnectionStage, ShardManager};
+use tokio::time::{Instant, timeout};
+
+use crate::app_state::AppState;
+
+/// The maximum time to wait for a single check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a shard may stay disconnected before Koe is considered not live.
+/// Shards resume or reconnect on their own, which usually takes much less than this.
+const DISCONNECTED_GRACE_PERIOD: Duration = Duration::from_mins(5);
+
+/// Whether restarting Koe may help, which is cheap enough to check on every liveness probe
+#[derive(Debug, Serialize)]
+pub struct Liveness {
+    pub live: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub shards: Vec<ShardReport>,
+}
+
+/// Remembers since when each shard has been disconnected, as observed by liveness checks.
+#[derive(Debug, Default)]
+pub struct ShardTracker {
+    disconnected_since: Mutex<HashMap<u32, Instant>>,
+}
+
+/// The state of Koe and the services it depends on
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Whether Koe can read messages aloud
+    pub ready: bool,
+    pub checks: Checks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Checks {
+    pub gateway: GatewayCheck,
+    pub storage: Check,
+    pub voicevox: VoicevoxCheck,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The time taken by the check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u128>,
+}
+
+impl Check {
+    fn new(error: Option<String>, latency_ms: Option<u128>) -> Self {
+        Self {
+            ok: error.is_none(),
+            error,
+            latency_ms,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GatewayCheck {
+    #[serde(flatten)]
+    pub check: Check,
+    pub shards: Vec<ShardReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShardReport {
+    pub id: u32,
+    #[serde(serialize_with = "serialize_display")]
+    pub stage: ConnectionStage,
+    /// The latency of the last heartbeat
+    pub latency_ms: Option<u128>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VoicevoxCheck {
+    #[serde(flatten)]
+    pub check: Check,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the speakers have been initialized
+    pub warmed_up: bool,
+}
+
+/// Checks that a shard is running and none has been disconnected for longer than
+/// [`DISCONNECTED_GRACE_PERIOD`], without calling the storage or the VOICEVOX ENGINE.
+pub async fn check_liveness(shard_manager: &ShardManager, tracker: &ShardTracker) -> Liveness {
+    let shards = list_shards(shard_manager).await;
+    let error = tracker
+        .check(&shards, Instant::now())
+        .err()
+        .map(|err| err.to_string());
+
+    Liveness {
+        live: error.is_none(),
+        error,
+        shards,
+    }
+}
+
+impl ShardTracker {
+    fn check(&self, shards: &[ShardReport], now: Instant) -> Result<()> {
+        if shards.is_empty() {
+            bail!("No shards are running");
+        }
+
+        let mut disconnected_since = self
+            .disconnected_since
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        disconnected_since.retain(|id, _| {
+            shards
+                .iter()
+                .any(|shard| shard.id == *id && shard.stage != ConnectionStage::Connected)
+        });
+
+        for shard in shards {
+            if shard.stage == ConnectionStage::Connected {
+                continue;
+            }
+            let since = *disconnected_since.entry(shard.id).or_insert(now);
+            if now.duration_since(since) > DISCONNECTED_GRACE_PERIOD {
+                bail!(
+                    "Shard {} has been {} for more than {:?}",
+                    shard.id,
+                    shard.stage,
+                    DISCONNECTED_GRACE_PERIOD
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the gateway shards, the storage and the VOICEVOX ENGINE concurrently.
+pub async fn check_readiness(state: &AppState, shard_manager: &ShardManager) -> Report {
+    let voicevox_client = state.voicevox_client();
+    let warmed_up = *state.warmed_up.borrow();
+
+    let (shards, (storage, _), (voicevox, version)) = tokio::join!(
+        list_shards(shard_manager),
+        run(state.storage.ping()),
+        run(voicevox_client.version()),
+    );
+    let gateway = Check::new(check_shards(&shards).err().map(|err| err.to_string()), None);
+
+    Report {
+        ready: gateway.ok && storage.ok && voicevox.ok && warmed_up,
+        checks: Checks {
+            gateway: GatewayCheck {
+                check: gateway,
+                shards,
+            },
+            storage,
+            voicevox: VoicevoxCheck {
+                check: voicevox,
+                version,
+                warmed_up,
+            },
+        },
+    }
+}
+
+/// Runs `check` with a timeout and returns the result along with the value it produced.
+async fn run<T, E: Into<anyhow::Error>>(
+    check: impl Future<Output = Result<T, E>>,
+) -> (Check, Option<T>) {
+    let started_at = Instant::now();
+    let result = timeout(CHECK_TIMEOUT, check).await;
+    let latency_ms = started_at.elapsed().as_millis();
+
+    let (value, error) = match result {
+        Ok(Ok(value)) => (Some(value), None),
+        Ok(Err(err)) => (None, Some(format!("{:#}", err.into()))),
+        Err(_) => (None, Some(format!("Timed out after {CHECK_TIMEOUT:?}"))),
+    };
+
+    (Check::new(error, Some(latency_ms)), value)
+}
+
+async fn list_shards(shard_manager: &ShardManager) -> Vec<ShardReport> {
+    let runners = shard_manager.runners.lock().await;
+
+    let mut shards = runners
+        .iter()
+        .map(|(id, info)| ShardReport {
+            id: id.0,
+            stage: info.stage,
+            latency_ms: info.latency.map(|latency| latency.as_millis()),
+        })
+        .collect::<Vec<_>>();
+    shards.sort_by_key(|shard| shard.id);
+
+    shards
+}
+
+/// Fails unless every shard is connected, which is required to receive messages.
+fn check_shards(shards: &[ShardReport]) -> Result<()> {
+    if shards.is_empty() {
+        bail!("No shards are running");
+    }
+    if let Some(shard) = shards
+        .iter()
+        .find(|shard| shard.stage != ConnectionStage::Connected)
+    {
+        bail!("Shard {} is {}", shard.id, shard.stage);
+    }
+
+    Ok(())
+}
+
+fn serialize_display<S: Serializer>(
+    value: &impl fmt::Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use serenity::gateway::ConnectionStage;
+use tokio::time::{Duration, Instant};
+
+use super::{DISCONNECTED_GRACE_PERIOD, ShardReport, ShardTracker};
+
+fn shard(id: u32, stage: ConnectionStage) -> ShardReport {
+    ShardReport {
+        id,
+        stage,
+        latency_ms: None,
+    }
+}
+
+#[test]
+fn no_shards_is_not_live() {
+    let tracker = ShardTracker::default();
+
+    assert!(tracker.check(&[], Instant::now()).is_err());
+}
+
+#[test]
+fn reconnecting_shard_is_live_within_grace_period() {
+    let tracker = ShardTracker::default();
+    let start = Instant::now();
+    let shards = [
+        shard(0, ConnectionStage::Connected),
+        shard(1, ConnectionStage::Resuming),
+    ];
+
+    assert!(tracker.check(&shards, start).is_ok());
+    assert!(
+        tracker
+            .check(&shards, start + DISCONNECTED_GRACE_PERIOD)
+            .is_ok()
+    );
+
+    let err = tracker
+        .check(
+            &shards,
+            start + DISCONNECTED_GRACE_PERIOD + Duration::from_secs(1),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Shard 1 has been resuming for more than {DISCONNECTED_GRACE_PERIOD:?}")
+    );
+}
+
+#[test]
+fn grace_period_restarts_after_shard_connects() {
+    let tracker = ShardTracker::default();
+    let start = Instant::now();
+
+    tracker
+        .check(&[shard(0, ConnectionStage::Connecting)], start)
+        .unwrap();
+    tracker
+        .check(
+            &[shard(0, ConnectionStage::Connected)],
+            start + Duration::from_secs(10),
+        )
+        .unwrap();
+
+    let later = start + DISCONNECTED_GRACE_PERIOD + Duration::from_secs(20);
+    assert!(
+        tracker
+            .check(&[shard(0, ConnectionStage::Disconnected)], later)
+            .is_ok()
+    );
+}
@@ -0,0 +1,116 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use serenity::gateway::ShardManager;
+use songbird::Songbird;
+use tracing::{error, info};
+
+use crate::{app_state::AppState, health, metrics, voice_call};
+
+#[derive(Clone)]
+struct HttpState {
+    app_state: Arc<AppState>,
+    songbird: Arc<Songbird>,
+    shard_manager: Arc<ShardManager>,
+    shard_tracker: Arc<health::ShardTracker>,
+}
+
+/// Starts the HTTP server for monitoring in the background.
+pub async fn spawn(
+    listen: SocketAddr,
+    app_state: Arc<AppState>,
+    songbird: Arc<Songbird>,
+    shard_manager: Arc<ShardManager>,
+) -> Result<()> {
+    let router = Router::new()
+        .route("/metrics", get(serve_metrics))
+        .route("/healthz", get(serve_healthz))
+        .route("/readyz", get(serve_readyz))
+        .with_state(HttpState {
+            app_state,
+            songbird,
+            shard_manager,
+            shard_tracker: Arc::default(),
+        });
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {listen}"))?;
+    info!("HTTP server listening on {listen}");
+
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router)
+            .await
+            .context("HTTP server stopped")
+        {
+            error!("{err:?}");
+        }
+    });
+
+    Ok(())
+}
+
+async fn serve_metrics(State(state): State<HttpState>) -> Response {
+    // Gauges that reflect the current state are updated on each scrape instead of on each change
+    let guild_ids = state
+        .app_state
+        .connected_guild_states
+        .iter()
+        .map(|entry| *entry.key())
+        .collect::<Vec<_>>();
+    metrics::set_connected_guilds(guild_ids.len());
+
+    let mut queue_lengths = Vec::with_capacity(guild_ids.len());
+    for guild_id in guild_ids {
+        if let Some(length) = voice_call::queue_length(&state.songbird, guild_id).await {
+            queue_lengths.push((guild_id.get(), length));
+        }
+    }
+    metrics::set_queue_lengths(queue_lengths);
+
+    match metrics::encode_text() {
+        Ok(body) => (
+            [(
+                header::CONTENT_TYPE,
+                "application/openmetrics-text; version=1.0.0; charset=utf-8",
+            )],
+            body,
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err.context("Failed to encode metrics"));
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Responds with 503 only when Koe has been disconnected from Discord for a while, which a restart
+/// may fix. The storage and VOICEVOX ENGINE are not checked, so that this stays cheap.
+async fn serve_healthz(State(state): State<HttpState>) -> Response {
+    let liveness = health::check_liveness(&state.shard_manager, &state.shard_tracker).await;
+    health_response(liveness.live, &liveness)
+}
+
+/// Responds with 503 unless Koe can read messages aloud.
+async fn serve_readyz(State(state): State<HttpState>) -> Response {
+    let report = health::check_readiness(&state.app_state, &state.shard_manager).await;
+    health_response(report.ready, &report)
+}
+
+fn health_response(ok: bool, report: &impl Serialize) -> Response {
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(report)).into_response()
+}
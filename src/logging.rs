@@ -0,0 +1,40 @@
+use anyhow::{Result, anyhow, bail};
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+
+/// The environment variable that selects the log format
+const FORMAT_VAR: &str = "KOE_LOG_FORMAT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, including the fields of the enclosing spans
+    Json,
+}
+
+/// Installs the global subscriber.
+///
+/// Log levels are controlled with `RUST_LOG` in the same syntax as before, e.g. `koe=debug`.
+/// When a span closes, its duration is logged at the level of the span so that the time taken by
+/// each stage of reading a message can be seen with `RUST_LOG=koe=debug`. The spans of frequent
+/// events are at DEBUG as well, so that their close events do not flood the logs at INFO.
+pub fn init() -> Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("error"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    match format()? {
+        Format::Text => builder.try_init(),
+        Format::Json => builder.json().try_init(),
+    }
+    .map_err(|err| anyhow!("Failed to initialize logging: {err}"))
+}
+
+fn format() -> Result<Format> {
+    match std::env::var(FORMAT_VAR).as_deref() {
+        Err(_) | Ok("" | "text") => Ok(Format::Text),
+        Ok("json") => Ok(Format::Json),
+        Ok(other) => bail!("Invalid {FORMAT_VAR} `{other}`: must be `text` or `json`"),
+    }
+}
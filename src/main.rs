@@ -1,24 +1,46 @@
+use std::sync::{Arc, RwLock, atomic::AtomicBool};
+
 use anyhow::{Context, Result};
 use dashmap::DashMap;
-use log::{error, info};
 use serenity::{
     Client,
-    model::{gateway::GatewayIntents, id::ApplicationId},
+    http::Http,
+    model::{
+        gateway::GatewayIntents,
+        id::{ApplicationId, GuildId},
+    },
+};
+use songbird::{SerenityInit, Songbird};
+use tokio::sync::watch;
+use tracing::info;
+use tts::voicevox::VoicevoxClient;
+
+use crate::{
+    commands::registration,
+    config::{Config, StorageBackend},
+    db::redis::migration::{self, RunOption},
 };
-use songbird::SerenityInit;
-use tokio::time::Duration;
-use tts::{speech::initialize_speakers, voicevox::VoicevoxClient};
 
 mod app_state;
+mod cli;
 mod commands;
 mod components;
 mod config;
 mod db;
 mod event_handler;
+mod health;
+mod http;
+mod logging;
 mod message;
+mod metrics;
+mod reload;
+mod session;
+mod shutdown;
 mod tts;
+mod util;
 mod voice_call;
 mod voice_state;
+mod warmup;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -26,48 +48,196 @@ async fn main() -> Result<()> {
         .install_default()
         .map_err(|_| anyhow::anyhow!("rustls crypto provider was already installed"))?;
 
-    env_logger::init();
+    logging::init()?;
+
+    let command = cli::parse()?;
 
     let config = config::load().await?;
     info!("Config loaded");
 
+    match command {
+        cli::Command::Run => run(config).await,
+        cli::Command::Migrate { dry_run } => migrate(config, dry_run).await,
+        cli::Command::Transfer { from, to } => transfer(config, from, to).await,
+        cli::Command::Commands { action, target } => manage_commands(config, action, target).await,
+    }
+}
+
+async fn run(config: Config) -> Result<()> {
+    let storage = db::connect(&config, config.storage.backend)
+        .await
+        .context("Failed to connect to storage")?;
+
+    let voicevox_client = VoicevoxClient::new(&config.voicevox)?;
+
     let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
 
-    let mut client = Client::builder(config.discord.bot_token, intents)
-        .event_handler(event_handler::Handler)
+    let songbird = Songbird::serenity();
+
+    let mut client = Client::builder(&config.discord.bot_token, intents)
+        .event_handler(event_handler::Handler {
+            command_scope: config.discord.command_scope,
+        })
         .application_id(ApplicationId::new(config.discord.client_id))
-        .register_songbird()
+        .register_songbird_with(Arc::clone(&songbird))
         .await
         .context("Failed to build serenity client")?;
 
-    app_state::initialize(
+    let state = app_state::initialize(
         &client,
         app_state::AppState {
-            redis_client: redis::Client::open(config.redis.url)?,
-            voicevox_client: VoicevoxClient::new(config.voicevox.api_base),
+            storage,
+            reloadable: RwLock::new(app_state::ReloadableState {
+                voicevox_client: Arc::new(voicevox_client),
+                global_dict: Arc::new(config.dict.global.clone()),
+                wait_for_warmup: config.voicevox.wait_for_warmup,
+                catchup: config.catchup,
+            }),
             connected_guild_states: DashMap::new(),
+            warmed_up: watch::Sender::new(false),
+            warmup_wait_expired: AtomicBool::new(false),
+            shutdown: watch::Sender::new(None),
         },
     )
     .await;
 
-    let d = client.data.clone();
-    tokio::spawn(async move {
-        tokio::time::sleep(Duration::from_secs(3)).await;
-        info!("Initializing speakers...");
-
-        let data = d.read().await;
-        let state = data.get::<app_state::AppState>().unwrap();
+    if let Some(http_config) = &config.http {
+        http::spawn(
+            http_config.listen,
+            Arc::clone(&state),
+            Arc::clone(&songbird),
+            Arc::clone(&client.shard_manager),
+        )
+        .await?;
+    }
 
-        if let Err(err) = initialize_speakers(&state.voicevox_client)
-            .await
-            .context("Failed to initialize speakers")
-        {
-            error!("{err:?}");
-        }
-    });
+    shutdown::spawn(
+        Arc::clone(&state),
+        songbird,
+        Arc::clone(&client.shard_manager),
+        Arc::clone(&client.cache),
+        config.shutdown,
+    )?;
+    reload::spawn(Arc::clone(&state), config)?;
+    warmup::spawn(state);
 
     info!("Starting client...");
     client.start().await.context("Client error occurred")?;
 
     Ok(())
 }
+
+async fn migrate(config: Config, dry_run: bool) -> Result<()> {
+    let redis_config = config
+        .redis
+        .context("`redis` must be configured to migrate Redis schema")?;
+    let mut conn = db::redis::connect(&redis_config).await?;
+
+    let current_version = migration::get_version(&mut conn).await?;
+    println!("Current schema version: {current_version}");
+
+    let migrations = migration::run(&mut conn, RunOption { dry_run })
+        .await
+        .context("Failed to migrate database")?;
+
+    if migrations.is_empty() {
+        println!("Database schema is up to date.");
+        return Ok(());
+    }
+
+    for migration in migrations {
+        let status = if dry_run { "Pending" } else { "Applied" };
+        println!(
+            "{status}: version {} - {}",
+            migration.version, migration.description
+        );
+    }
+
+    Ok(())
+}
+
+async fn transfer(config: Config, from: StorageBackend, to: StorageBackend) -> Result<()> {
+    let source = db::connect(&config, from)
+        .await
+        .with_context(|| format!("Failed to connect to {from} storage"))?;
+    let destination = db::connect(&config, to)
+        .await
+        .with_context(|| format!("Failed to connect to {to} storage"))?;
+
+    let dump = db::dump::transfer(source.as_ref(), destination.as_ref())
+        .await
+        .with_context(|| format!("Failed to transfer data from {from} to {to}"))?;
+
+    println!("Transferred data from {from} to {to}:");
+    println!(
+        "  Dictionaries: {} ({} words)",
+        dump.dicts.len(),
+        dump.dicts.iter().map(|d| d.entries.len()).sum::<usize>()
+    );
+    println!("  Voices: {}", dump.voices.len());
+    println!("  Guild settings: {}", dump.settings.len());
+    println!("  Ignore targets: {}", dump.ignore_targets.len());
+    println!("  Name readings: {}", dump.names.len());
+    println!("  Opted-out users: {}", dump.opted_out_users.len());
+
+    Ok(())
+}
+
+async fn manage_commands(
+    config: Config,
+    action: cli::CommandsAction,
+    target: cli::CommandsTarget,
+) -> Result<()> {
+    let http = Http::new(&config.discord.bot_token);
+    http.set_application_id(ApplicationId::new(config.discord.client_id));
+
+    let guild_ids = match target {
+        cli::CommandsTarget::Global => return manage_global_commands(&config, &http, action).await,
+        cli::CommandsTarget::AllGuilds => registration::list_guilds(&http).await?,
+        cli::CommandsTarget::Guild(guild_id) => vec![GuildId::new(guild_id)],
+    };
+
+    for &guild_id in &guild_ids {
+        match action {
+            cli::CommandsAction::Register => registration::register_guild(&http, guild_id).await?,
+            cli::CommandsAction::Clear => registration::clear_guild(&http, guild_id).await?,
+        }
+    }
+
+    println!(
+        "{} slash commands in {} guilds",
+        action_done(action),
+        guild_ids.len()
+    );
+
+    Ok(())
+}
+
+async fn manage_global_commands(
+    config: &Config,
+    http: &Http,
+    action: cli::CommandsAction,
+) -> Result<()> {
+    // The hash is saved so that Koe does not register the same commands again on startup
+    let storage = db::connect(config, config.storage.backend)
+        .await
+        .context("Failed to connect to storage")?;
+
+    match action {
+        cli::CommandsAction::Register => {
+            registration::register_global(http, storage.as_ref()).await?;
+        }
+        cli::CommandsAction::Clear => registration::clear_global(http, storage.as_ref()).await?,
+    }
+
+    println!("{} global slash commands", action_done(action));
+
+    Ok(())
+}
+
+const fn action_done(action: cli::CommandsAction) -> &'static str {
+    match action {
+        cli::CommandsAction::Register => "Registered",
+        cli::CommandsAction::Clear => "Removed",
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context as _, Result};
+use serenity::{client::Context, model::id::GuildId};
+
+use crate::{
+    app_state::AppState,
+    db::setting::GuildSetting,
+    tts::speech::{PresetId, SpeechRequest, make_speech},
+    voice_call::{self, TrackMetadata},
+};
+
+/// 読み上げ待ちのメッセージ1件あたりの話速の増加量
+const SPEEDUP_PER_PENDING_MESSAGE: f64 = 0.05;
+
+/// 話速の倍率の上限
+const MAX_SPEED_SCALE: f64 = 1.6;
+
+/// サーバーの設定に従って古い読み上げ待ちのメッセージを省略し、次に追加するメッセージの話速の倍率を返す
+///
+/// `available_preset_ids`はVOICEVOX ENGINEで使えるプリセットで、省略したことを読み上げる声を選ぶために使う。
+pub async fn apply(
+    ctx: &Context,
+    state: &AppState,
+    setting: &GuildSetting,
+    guild_id: GuildId,
+    available_preset_ids: &[PresetId],
+) -> Result<f64> {
+    let threshold = setting.catchup_threshold as usize;
+    if threshold > 0 && voice_call::count_pending(ctx, guild_id).await? >= threshold {
+        let dropped_count = voice_call::drop_oldest_pending(ctx, guild_id, threshold / 2).await?;
+
+        if dropped_count > 0 {
+            let text = format!("他{dropped_count}件のメッセージ");
+            let preset_id = summary_preset_id(state, available_preset_ids)?;
+            let audio = make_speech(
+                &state.voicevox_client(),
+                SpeechRequest {
+                    text: text.clone(),
+                    preset_id,
+                    speed_scale: 1.0,
+                },
+            )
+            .await?;
+
+            let current_user = ctx.cache.current_user().clone();
+            voice_call::enqueue_next(
+                ctx,
+                guild_id,
+                audio,
+                TrackMetadata {
+                    author_id: current_user.id,
+                    author_name: current_user.display_name().to_string(),
+                    text,
+                    summarized_count: Some(dropped_count),
+                },
+            )
+            .await?;
+        }
+    }
+
+    if !setting.catchup_speedup {
+        return Ok(1.0);
+    }
+
+    let pending_count = voice_call::count_pending(ctx, guild_id).await?;
+    let pending_count = f64::from(u32::try_from(pending_count).unwrap_or(u32::MAX));
+
+    Ok((1.0 + SPEEDUP_PER_PENDING_MESSAGE * pending_count).min(MAX_SPEED_SCALE))
+}
+
+/// 省略したことを読み上げるプリセットを返す
+///
+/// メンバーの声で読み上げるとそのメンバーが発言したように聞こえるため、
+/// 設定されたプリセットか、VOICEVOX ENGINEの最初のプリセットを使う。
+fn summary_preset_id(state: &AppState, available_preset_ids: &[PresetId]) -> Result<PresetId> {
+    if let Some(preset_id) = state.catchup().summary_preset_id {
+        return Ok(PresetId(preset_id));
+    }
+
+    available_preset_ids
+        .first()
+        .copied()
+        .context("No presets available")
+}
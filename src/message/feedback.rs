@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use serenity::{
+    client::Context,
+    model::{
+        channel::ReactionType,
+        id::{ChannelId, GuildId, MessageId},
+    },
+};
+
+use crate::{
+    app_state::AppState,
+    db::{StorageUnavailable, setting::FailureFeedback},
+    tts::voicevox::VoicevoxError,
+    voice_call::QueueFull,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// 読み上げられなかったメッセージに付けるリアクション
+const REACTION: &str = "⚠️";
+
+/// 理由を送信する最短の間隔
+/// これより短い間隔で失敗した場合は、代わりにリアクションを付ける
+const NOTICE_INTERVAL: Duration = Duration::from_mins(1);
+
+/// 読み上げられなかったメッセージ
+#[allow(clippy::struct_field_names)]
+pub struct FailedMessage {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+}
+
+/// サーバーの設定に従って、メッセージを読み上げられなかったことをメンバーに知らせる
+///
+/// `last_notice`は最後に理由を送信した時刻で、送信の間隔を制限するために使う。
+pub async fn report(
+    ctx: &Context,
+    state: &AppState,
+    failed: &FailedMessage,
+    err: &anyhow::Error,
+    last_notice: &mut Option<Instant>,
+) -> Result<()> {
+    // 保存先に接続できずに失敗した場合もあるため、設定を読み込めなければデフォルトの動作とする
+    let feedback = state
+        .storage
+        .get_setting(crate::db::setting::GetOption {
+            guild_id: failed.guild_id.into(),
+        })
+        .await
+        .map(|setting| setting.failure_feedback)
+        .unwrap_or_default();
+
+    match action(feedback, *last_notice) {
+        None => {}
+        Some(Action::Notice) => {
+            let text = format!(
+                "{REACTION} {} を読み上げられませんでした: {}",
+                failed
+                    .message_id
+                    .link(failed.channel_id, Some(failed.guild_id)),
+                reason(err)
+            );
+            failed
+                .channel_id
+                .say(&ctx.http, text)
+                .await
+                .context("Failed to send failure notice")?;
+            *last_notice = Some(Instant::now());
+        }
+        Some(Action::Reaction) => {
+            ctx.http
+                .create_reaction(
+                    failed.channel_id,
+                    failed.message_id,
+                    &ReactionType::Unicode(REACTION.to_string()),
+                )
+                .await
+                .context("Failed to add failure reaction")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 失敗を知らせる方法
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Notice,
+    Reaction,
+}
+
+/// サーバーの設定と最後に理由を送信した時刻から、失敗を知らせる方法を決める
+fn action(feedback: FailureFeedback, last_notice: Option<Instant>) -> Option<Action> {
+    match feedback {
+        FailureFeedback::Off => None,
+        FailureFeedback::Notice
+            if last_notice.is_none_or(|sent_at| sent_at.elapsed() >= NOTICE_INTERVAL) =>
+        {
+            Some(Action::Notice)
+        }
+        FailureFeedback::Reaction | FailureFeedback::Notice => Some(Action::Reaction),
+    }
+}
+
+/// メンバーに表示する失敗の理由
+fn reason(err: &anyhow::Error) -> &'static str {
+    if err.downcast_ref::<StorageUnavailable>().is_some() {
+        return "設定を読み込めませんでした。しばらくしてから再度お試しください。";
+    }
+    if err.downcast_ref::<QueueFull>().is_some() {
+        return "読み上げ待ちのメッセージが多すぎます。";
+    }
+
+    match err.downcast_ref::<VoicevoxError>() {
+        Some(VoicevoxError::Unreachable(_)) => "音声合成エンジンが応答しませんでした。",
+        Some(VoicevoxError::InvalidPreset(_)) => {
+            "声の設定が無効です。`/voice`で声を設定し直してください。"
+        }
+        Some(VoicevoxError::Validation { .. }) => {
+            "音声合成エンジンがこのメッセージを受け付けませんでした。"
+        }
+        Some(VoicevoxError::Status { .. } | VoicevoxError::InvalidResponse(_)) => {
+            "音声合成エンジンでエラーが発生しました。"
+        }
+        None => "読み上げ中にエラーが発生しました。",
+    }
+}
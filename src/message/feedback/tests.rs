@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+
+use super::{Action, NOTICE_INTERVAL, action, reason};
+use crate::{
+    db::{StorageUnavailable, setting::FailureFeedback},
+    tts::voicevox::VoicevoxError,
+    voice_call::QueueFull,
+};
+
+#[test]
+fn notice_is_sent_when_no_notice_has_been_sent() {
+    assert_eq!(action(FailureFeedback::Notice, None), Some(Action::Notice));
+}
+
+#[test]
+fn notice_falls_back_to_reaction_within_interval() {
+    let last_notice = Instant::now();
+
+    assert_eq!(
+        action(FailureFeedback::Notice, Some(last_notice)),
+        Some(Action::Reaction)
+    );
+}
+
+#[test]
+fn notice_is_sent_again_after_interval() {
+    let Some(last_notice) = Instant::now().checked_sub(NOTICE_INTERVAL + Duration::from_secs(1))
+    else {
+        // 起動直後などで過去の時刻を表せない環境では確認できない
+        return;
+    };
+
+    assert_eq!(
+        action(FailureFeedback::Notice, Some(last_notice)),
+        Some(Action::Notice)
+    );
+}
+
+#[test]
+fn reaction_and_off_ignore_last_notice() {
+    assert_eq!(
+        action(FailureFeedback::Reaction, None),
+        Some(Action::Reaction)
+    );
+    assert_eq!(action(FailureFeedback::Off, None), None);
+    assert_eq!(action(FailureFeedback::Off, Some(Instant::now())), None);
+}
+
+#[test]
+fn reason_describes_cause_through_context() {
+    let storage = Err::<(), _>(anyhow::anyhow!("connection refused"))
+        .context(StorageUnavailable)
+        .context("Failed to read message")
+        .unwrap_err();
+    assert_eq!(
+        reason(&storage),
+        "設定を読み込めませんでした。しばらくしてから再度お試しください。"
+    );
+
+    let queue_full = anyhow::Error::from(QueueFull).context("Failed to read message");
+    assert_eq!(
+        reason(&queue_full),
+        "読み上げ待ちのメッセージが多すぎます。"
+    );
+
+    let preset = anyhow::Error::from(VoicevoxError::InvalidPreset(1))
+        .context("Failed to execute Text-to-Speech");
+    assert_eq!(
+        reason(&preset),
+        "声の設定が無効です。`/voice`で声を設定し直してください。"
+    );
+
+    let validation = anyhow::Error::from(VoicevoxError::Validation {
+        detail: serde_json::Value::String("invalid".to_string()),
+    });
+    assert_eq!(
+        reason(&validation),
+        "音声合成エンジンがこのメッセージを受け付けませんでした。"
+    );
+
+    let status = anyhow::Error::from(VoicevoxError::Status {
+        status: reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+        body: String::new(),
+    });
+    assert_eq!(reason(&status), "音声合成エンジンでエラーが発生しました。");
+
+    let other = anyhow::anyhow!("something went wrong");
+    assert_eq!(reason(&other), "読み上げ中にエラーが発生しました。");
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use serenity::{
+    client::Context,
+    model::{channel::Message, id::GuildId},
+};
+
+use crate::{
+    db::{
+        Storage,
+        ignore::{GetAllOption, IgnoreTarget},
+        setting::GuildSetting,
+        user::GetOptOutOption,
+    },
+    voice_state,
+};
+
+/// 送信者やサーバーの設定に従い、メッセージを読み上げない場合は`true`を返す
+pub async fn should_ignore(
+    ctx: &Context,
+    storage: &dyn Storage,
+    setting: &GuildSetting,
+    guild_id: GuildId,
+    msg: &Message,
+) -> Result<bool> {
+    let (opted_out, ignore_list) = tokio::try_join!(
+        storage.get_opt_out(GetOptOutOption {
+            user_id: msg.author.id.into(),
+        }),
+        storage.get_ignore_targets(GetAllOption {
+            guild_id: guild_id.into(),
+        }),
+    )?;
+    if opted_out {
+        return Ok(true);
+    }
+
+    let role_ids = msg
+        .member
+        .as_ref()
+        .map(|member| member.roles.as_slice())
+        .unwrap_or_default();
+
+    let is_ignored = ignore_list.iter().any(|target| match target {
+        IgnoreTarget::User(user_id) => *user_id == msg.author.id.get(),
+        IgnoreTarget::Role(role_id) => role_ids.iter().any(|id| id.get() == *role_id),
+    });
+    if is_ignored {
+        return Ok(true);
+    }
+
+    if setting.vc_members_only
+        && !voice_state::is_in_current_voice_channel(ctx, guild_id, msg.author.id)?
+    {
+        return Ok(true);
+    }
+
+    Ok(false)
+}
@@ -1,17 +1,27 @@
+mod catchup;
+mod feedback;
+mod ignore;
 mod read;
 
-use anyhow::{Context as _, Result};
-use log::trace;
+use anyhow::{Context as _, Result, anyhow};
 use rand::seq::IndexedRandom;
-use serenity::{client::Context, model::channel::Message};
+use serenity::{
+    client::Context,
+    model::{channel::Message, id::GuildId},
+};
+use tokio::sync::mpsc;
+use tracing::{Instrument as _, debug_span, error, info, instrument, trace};
 
 use crate::{
-    app_state,
-    db::{self, voice::GetOption},
-    tts::speech::{SpeechRequest, list_preset_ids, make_speech},
-    voice_call,
+    app_state::{self, AppState},
+    config::InFlightPolicy,
+    db::{self, setting::GuildSetting, voice::GetOption},
+    metrics::{self, SkipReason},
+    tts::speech::{PresetId, SpeechRequest, list_preset_ids, make_speech},
+    voice_call::{self, QueueFull, TrackMetadata},
 };
 
+#[instrument(name = "filter", level = "debug", skip_all)]
 pub async fn handle(ctx: &Context, msg: Message) -> Result<()> {
     let Some(guild_id) = msg.guild_id else {
         return Ok(());
@@ -22,68 +32,266 @@ pub async fn handle(ctx: &Context, msg: Message) -> Result<()> {
     }
 
     let state = app_state::get(ctx).await?;
-    let mut guild_state = state
-        .connected_guild_states
-        .get_mut(&guild_id)
+    let guild_state = state
+        .guild_state(guild_id)
         .with_context(|| format!("Guild state not found for guild {guild_id}"))?;
 
     if guild_state.bound_text_channel != msg.channel_id {
         return Ok(());
     }
 
+    metrics::message_seen();
+
+    if state.is_shutting_down() {
+        metrics::message_skipped(SkipReason::ShuttingDown);
+        return Ok(());
+    }
+
     // Skip message from Koe itself
     if msg.author.id == ctx.cache.current_user().id {
+        metrics::message_skipped(SkipReason::OwnMessage);
         return Ok(());
     }
 
     // Skip message that starts with semicolon
     if msg.content.starts_with(';') {
+        metrics::message_skipped(SkipReason::CommandPrefix);
         return Ok(());
     }
 
-    let mut conn = state
-        .redis_client
-        .get_multiplexed_async_connection()
+    let setting = state
+        .storage
+        .get_setting(db::setting::GetOption {
+            guild_id: guild_id.into(),
+        })
         .await?;
 
-    let text = read::build_read_text(
+    if ignore::should_ignore(ctx, state.storage.as_ref(), &setting, guild_id, &msg).await? {
+        trace!("Ignored message {} in guild {guild_id}", msg.id);
+        metrics::message_skipped(SkipReason::Ignored);
+        return Ok(());
+    }
+
+    guild_state
+        .message_sender
+        .send(QueuedMessage { msg, setting })
+        .map_err(|_| anyhow!("Reader task for guild {guild_id} has already stopped"))?;
+
+    Ok(())
+}
+
+/// A message waiting to be read aloud, with the setting of the guild fetched when it was sent
+pub struct QueuedMessage {
+    pub msg: Message,
+    pub setting: GuildSetting,
+}
+
+/// Spawns a task that reads aloud the messages sent to the returned sender one at a time, so that
+/// messages in a guild are spoken in the order they were sent without holding any lock.
+///
+/// When Koe starts shutting down, the task reads the messages already sent or drops them depending
+/// on [`InFlightPolicy`], and then stops.
+pub fn spawn_reader(
+    ctx: Context,
+    state: &AppState,
+    guild_id: GuildId,
+) -> mpsc::UnboundedSender<QueuedMessage> {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<QueuedMessage>();
+    let mut shutdown = state.shutdown.subscribe();
+
+    tokio::spawn(async move {
+        let mut last_message_read = None;
+        let mut last_failure_notice = None;
+
+        loop {
+            let msg = tokio::select! {
+                biased;
+                policy = shutdown.wait_for(Option::is_some) => match policy.map(|policy| *policy) {
+                    Ok(Some(InFlightPolicy::Finish)) => receiver.try_recv().ok(),
+                    _ => None,
+                },
+                msg = receiver.recv() => msg,
+            };
+            let Some(QueuedMessage { msg, setting }) = msg else {
+                break;
+            };
+
+            let span = debug_span!(
+                "read",
+                guild_id = guild_id.get(),
+                channel_id = msg.channel_id.get(),
+                message_id = msg.id.get(),
+                user_id = msg.author.id.get(),
+            );
+
+            let failed = feedback::FailedMessage {
+                guild_id,
+                channel_id: msg.channel_id,
+                message_id: msg.id,
+            };
+
+            if let Err(err) = read(&ctx, guild_id, msg, &setting, &mut last_message_read)
+                .instrument(span.clone())
+                .await
+                .context("Failed to read message")
+            {
+                if err.downcast_ref::<QueueFull>().is_some() {
+                    span.in_scope(|| {
+                        info!("Queue is full in guild {guild_id}. Dropping the message.");
+                    });
+                    metrics::message_skipped(SkipReason::QueueFull);
+                } else {
+                    span.in_scope(|| error!("{err:?}"));
+                    metrics::message_skipped(SkipReason::Failed);
+                }
+
+                if let Err(err) = report_failure(&ctx, &failed, &err, &mut last_failure_notice)
+                    .instrument(span.clone())
+                    .await
+                {
+                    span.in_scope(|| error!("{err:?}"));
+                }
+            }
+        }
+
+        trace!("Reader task for guild {guild_id} stopped");
+    });
+
+    sender
+}
+
+async fn report_failure(
+    ctx: &Context,
+    failed: &feedback::FailedMessage,
+    err: &anyhow::Error,
+    last_notice: &mut Option<std::time::Instant>,
+) -> Result<()> {
+    let state = app_state::get(ctx).await?;
+    feedback::report(ctx, &state, failed, err, last_notice)
+        .await
+        .context("Failed to report that the message could not be read")
+}
+
+async fn read(
+    ctx: &Context,
+    guild_id: GuildId,
+    msg: Message,
+    setting: &GuildSetting,
+    last_message_read: &mut Option<Message>,
+) -> Result<()> {
+    // Koe may have left the voice channel while the message was waiting in the queue
+    if !voice_call::is_connected(ctx, guild_id).await? {
+        metrics::message_skipped(SkipReason::Disconnected);
+        return Ok(());
+    }
+
+    let state = app_state::get(ctx).await?;
+    state.wait_for_warmup().await;
+
+    let (author_name, text) = build_text(
         ctx,
-        &mut conn,
+        &state,
         guild_id,
         &msg,
-        guild_state.last_message_read.as_ref(),
+        last_message_read.as_ref(),
+        setting,
     )
     .await?;
-    trace!("Built text: {:?}", &text);
 
     if text.is_empty() {
         trace!("Text is empty");
+        metrics::message_skipped(SkipReason::Empty);
         return Ok(());
     }
 
-    let available_preset_ids = list_preset_ids(&state.voicevox_client).await?;
+    let (preset_id, speed_scale) = lookup_preset(ctx, &state, setting, guild_id, &msg).await?;
+
+    let audio = make_speech(
+        &state.voicevox_client(),
+        SpeechRequest {
+            text: text.clone(),
+            preset_id,
+            speed_scale,
+        },
+    )
+    .await
+    .context("Failed to execute Text-to-Speech")?;
+
+    voice_call::enqueue(
+        ctx,
+        guild_id,
+        audio,
+        TrackMetadata {
+            author_id: msg.author.id,
+            author_name,
+            text,
+            summarized_count: None,
+        },
+        state.catchup().max_queue_length,
+    )
+    .await?;
+
+    metrics::message_read();
+
+    *last_message_read = Some(msg);
+
+    Ok(())
+}
+
+/// Returns the name of the author and the text to read aloud.
+#[instrument(level = "debug", skip_all)]
+async fn build_text(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    msg: &Message,
+    last_message_read: Option<&Message>,
+    setting: &GuildSetting,
+) -> Result<(String, String)> {
+    let author_name = read::build_author_name(ctx, state.storage.as_ref(), guild_id, msg).await?;
+    let input = read::build_read_input(
+        ctx,
+        state,
+        guild_id,
+        msg,
+        &author_name,
+        last_message_read,
+        setting,
+    )
+    .await?;
+    let text = read::build_read_text(&input)?;
+    trace!("Built text: {:?}", &text);
+
+    Ok((author_name, text))
+}
+
+/// Returns the preset of the author and the speed to read at.
+#[instrument(level = "debug", skip_all)]
+async fn lookup_preset(
+    ctx: &Context,
+    state: &AppState,
+    setting: &GuildSetting,
+    guild_id: GuildId,
+    msg: &Message,
+) -> Result<(PresetId, f64)> {
+    let available_preset_ids = list_preset_ids(&state.voicevox_client()).await?;
     let fallback_preset_id = available_preset_ids
         .choose(&mut rand::rng())
         .context("No presets available")?
         .into();
-    let preset_id = db::voice::get(
-        &mut conn,
-        GetOption {
+    let preset_id = state
+        .storage
+        .get_voice(GetOption {
             guild_id: guild_id.into(),
             user_id: msg.author.id.into(),
             fallback: fallback_preset_id,
-        },
-    )
-    .await?
-    .into();
+        })
+        .await?
+        .into();
 
-    let audio = make_speech(&state.voicevox_client, SpeechRequest { text, preset_id })
+    let speed_scale = catchup::apply(ctx, state, setting, guild_id, &available_preset_ids)
         .await
-        .context("Failed to execute Text-to-Speech")?;
-
-    voice_call::enqueue(ctx, guild_id, audio).await?;
+        .context("Failed to apply catch-up policy")?;
 
-    guild_state.last_message_read = Some(msg);
-
-    Ok(())
+    Ok((preset_id, speed_scale))
 }
@@ -0,0 +1,151 @@
+//! 読み上げる文章を組み立てる個々の処理
+//!
+//! 処理を追加する場合は、[`ReadFilter`]に種類を追加し、[`get`]で実装を返すようにすること。
+
+use std::collections::HashMap;
+
+use aho_corasick::{AhoCorasickBuilder, MatchKind};
+use anyhow::Result;
+use discord_md::generate::{ToMarkdownString, ToMarkdownStringOption};
+use regex::{Captures, Regex};
+
+use super::text::{Mention, ReadInput, mention_regex, parse_mention, regex};
+use crate::db::setting::ReadFilter;
+
+/// 読み上げる文章に適用する処理
+pub trait TextFilter: Send + Sync {
+    /// `text`を変換して返す
+    fn apply(&self, text: String, input: &ReadInput) -> Result<String>;
+}
+
+/// 処理の種類に対応する実装を返す
+pub fn get(kind: ReadFilter) -> &'static dyn TextFilter {
+    match kind {
+        ReadFilter::Mention => &MentionFilter,
+        ReadFilter::CustomEmoji => &CustomEmojiFilter,
+        ReadFilter::Markdown => &MarkdownFilter,
+        ReadFilter::Url => &UrlFilter,
+        ReadFilter::AuthorName => &AuthorNameFilter,
+        ReadFilter::Dictionary => &DictionaryFilter,
+        ReadFilter::Truncate => &TruncateFilter,
+    }
+}
+
+/// ID表記されたメンションやチャンネル名を読める形に書き換える
+struct MentionFilter;
+
+impl TextFilter for MentionFilter {
+    fn apply(&self, text: String, input: &ReadInput) -> Result<String> {
+        let names = &input.mentions;
+
+        let text = mention_regex().replace_all(&text, |caps: &Captures| {
+            let Some(mention) = parse_mention(caps) else {
+                return caps[0].to_string();
+            };
+
+            match mention {
+                Mention::User(id) => names
+                    .users
+                    .get(&id)
+                    .map_or_else(|| "@invalid-user".to_string(), |name| format!("@{name}")),
+                Mention::Role(id) => names
+                    .roles
+                    .get(&id)
+                    .map_or_else(|| "@deleted-role".to_string(), |name| format!("@{name}")),
+                Mention::Channel(id) => names
+                    .channels
+                    .get(&id)
+                    .map_or_else(|| "#deleted-channel".to_string(), |name| format!("#{name}")),
+            }
+        });
+
+        Ok(text.into())
+    }
+}
+
+/// カスタム絵文字を読める形に置き換える
+struct CustomEmojiFilter;
+
+impl TextFilter for CustomEmojiFilter {
+    fn apply(&self, text: String, _input: &ReadInput) -> Result<String> {
+        Ok(custom_emoji_regex().replace_all(&text, "$1").into())
+    }
+}
+
+/// Markdownの書式とスポイラーを取り除く
+struct MarkdownFilter;
+
+impl TextFilter for MarkdownFilter {
+    fn apply(&self, text: String, _input: &ReadInput) -> Result<String> {
+        Ok(discord_md::parse(&text).to_markdown_string(
+            &ToMarkdownStringOption::new()
+                .omit_format(true)
+                .omit_spoiler(true),
+        ))
+    }
+}
+
+/// メッセージのURLを除去
+struct UrlFilter;
+
+impl TextFilter for UrlFilter {
+    fn apply(&self, text: String, _input: &ReadInput) -> Result<String> {
+        Ok(url_regex().replace_all(&text, "、").into())
+    }
+}
+
+/// 送信者の名前を先頭に付ける
+struct AuthorNameFilter;
+
+impl TextFilter for AuthorNameFilter {
+    fn apply(&self, text: String, input: &ReadInput) -> Result<String> {
+        if input.read_author_name {
+            Ok(format!("{}。{text}", input.author_name))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+/// 辞書に登録されている語句を読み替える
+struct DictionaryFilter;
+
+impl TextFilter for DictionaryFilter {
+    fn apply(&self, text: String, input: &ReadInput) -> Result<String> {
+        replace_words_on_dict(&input.dict, &text)
+    }
+}
+
+fn replace_words_on_dict(dict: &HashMap<String, String>, text: &str) -> Result<String> {
+    let (word_list, read_as_list): (Vec<_>, Vec<_>) = dict.iter().unzip();
+
+    let ac = AhoCorasickBuilder::new()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(word_list)?;
+
+    Ok(ac.replace_all(text, &read_as_list))
+}
+
+/// 文字数を`input.max_length`文字に制限する
+struct TruncateFilter;
+
+impl TextFilter for TruncateFilter {
+    fn apply(&self, text: String, input: &ReadInput) -> Result<String> {
+        const OMITTED: &str = "、以下略";
+
+        if text.chars().count() > input.max_length {
+            let length = input.max_length.saturating_sub(OMITTED.chars().count());
+            Ok(text.chars().take(length).collect::<String>() + OMITTED)
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+fn url_regex() -> &'static Regex {
+    regex!(r"https?://\S\S+")
+}
+
+fn custom_emoji_regex() -> &'static Regex {
+    regex!(r"<(:\w+:)\d+>")
+}
@@ -0,0 +1,200 @@
+mod filter;
+#[cfg(test)]
+mod tests;
+mod text;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serenity::{
+    client::Context,
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId, RoleId, UserId},
+    },
+};
+pub use text::build_read_text;
+use text::{MAX_LENGTH, Mention, MentionNames, ReadInput};
+
+use crate::{
+    app_state::AppState,
+    db::{
+        self, Storage,
+        dict::{DictScope, GetAllOption},
+        setting::GuildSetting,
+    },
+};
+
+/// 読み上げる文章を組み立てるために必要な情報を、Discordとデータベースから集める
+pub async fn build_read_input(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    msg: &Message,
+    author_name: &str,
+    last_msg: Option<&Message>,
+    setting: &GuildSetting,
+) -> Result<ReadInput> {
+    let storage = state.storage.as_ref();
+    let mentions = get_mention_names(ctx, storage, guild_id, msg).await?;
+    let dict = get_dict(storage, guild_id, msg.author.id, &state.global_dict()).await?;
+
+    Ok(ReadInput {
+        content: msg.content.clone(),
+        author_name: author_name.to_string(),
+        read_author_name: should_read_author_name(msg, last_msg),
+        mentions,
+        dict,
+        max_length: MAX_LENGTH,
+        filters: setting.read_filters.clone(),
+    })
+}
+
+fn should_read_author_name(msg: &Message, last_msg: Option<&Message>) -> bool {
+    let Some(last_msg) = last_msg else {
+        return true;
+    };
+
+    msg.author != last_msg.author
+        || (msg.timestamp.unix_timestamp() - last_msg.timestamp.unix_timestamp()) > 10
+}
+
+/// 送信者の名前を返す。読み方が設定されている場合はそれを返す。
+pub async fn build_author_name(
+    ctx: &Context,
+    storage: &dyn Storage,
+    guild_id: GuildId,
+    msg: &Message,
+) -> Result<String> {
+    let reading = storage
+        .get_names(db::name::GetOption {
+            guild_id: guild_id.into(),
+            user_ids: vec![msg.author.id.into()],
+        })
+        .await?
+        .pop()
+        .flatten();
+
+    if let Some(reading) = reading {
+        return Ok(reading);
+    }
+
+    let name = msg
+        .author_nick(&ctx.http)
+        .await
+        .unwrap_or_else(|| msg.author.display_name().to_string());
+    Ok(name)
+}
+
+/// メッセージでメンションされたユーザー、ロール、チャンネルの名前を返す。
+/// ユーザーの名前の読み方が設定されている場合は、読み方を名前とする。
+async fn get_mention_names(
+    ctx: &Context,
+    storage: &dyn Storage,
+    guild_id: GuildId,
+    msg: &Message,
+) -> Result<MentionNames> {
+    let mut user_ids = Vec::new();
+    let mut role_ids = Vec::new();
+    let mut channel_ids = Vec::new();
+    for mention in text::parse_mentions(&msg.content) {
+        match mention {
+            Mention::User(id) if !user_ids.contains(&id) => user_ids.push(id),
+            Mention::Role(id) => role_ids.push(id),
+            Mention::Channel(id) => channel_ids.push(id),
+            Mention::User(_) => {}
+        }
+    }
+
+    let readings = storage
+        .get_names(db::name::GetOption {
+            guild_id: guild_id.into(),
+            user_ids: user_ids.clone(),
+        })
+        .await?;
+
+    let mut names = MentionNames::default();
+
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        for &id in &role_ids {
+            if let Some(role) = guild.roles.get(&RoleId::new(id)) {
+                names.roles.insert(id, role.name.clone());
+            }
+        }
+
+        for &id in &channel_ids {
+            let channel_id = ChannelId::new(id);
+            let name = guild
+                .channels
+                .get(&channel_id)
+                .map(|c| &c.name)
+                .or_else(|| {
+                    guild
+                        .threads
+                        .iter()
+                        .find(|thread| thread.id == channel_id)
+                        .map(|thread| &thread.name)
+                });
+            if let Some(name) = name {
+                names.channels.insert(id, name.clone());
+            }
+        }
+    }
+
+    for (id, reading) in user_ids.into_iter().zip(readings) {
+        if let Some(name) = reading.or_else(|| user_name(ctx, guild_id, msg, UserId::new(id))) {
+            names.users.insert(id, name);
+        }
+    }
+
+    Ok(names)
+}
+
+/// ユーザーのサーバーでの表示名を返す
+fn user_name(ctx: &Context, guild_id: GuildId, msg: &Message, user_id: UserId) -> Option<String> {
+    if let Some(guild) = ctx.cache.guild(guild_id)
+        && let Some(member) = guild.members.get(&user_id)
+    {
+        return Some(member.display_name().to_string());
+    }
+
+    if let Some(user) = ctx.cache.user(user_id) {
+        return Some(user.name.clone());
+    }
+
+    msg.mentions
+        .iter()
+        .find(|user| user.id == user_id)
+        .map(|user| user.name.clone())
+}
+
+/// 読み替えに使う辞書を返す。
+/// 同じ語句が複数の辞書に登録されている場合は、ユーザーの辞書、サーバーの辞書、全体の辞書の順に優先する。
+async fn get_dict(
+    storage: &dyn Storage,
+    guild_id: GuildId,
+    user_id: UserId,
+    global_dict: &HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let guild_dict = storage
+        .get_dict(GetAllOption {
+            scope: DictScope::Guild {
+                guild_id: guild_id.into(),
+            },
+        })
+        .await?;
+    let user_dict = storage
+        .get_dict(GetAllOption {
+            scope: DictScope::User {
+                guild_id: guild_id.into(),
+                user_id: user_id.into(),
+            },
+        })
+        .await?;
+
+    let mut dict = global_dict.clone();
+    dict.extend(guild_dict);
+    dict.extend(user_dict);
+
+    Ok(dict)
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::text::{MAX_LENGTH, Mention, MentionNames, ReadInput, build_read_text, parse_mentions};
+use crate::db::setting::ReadFilter;
+
+/// `testdata/golden.yaml`のテストケース
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    name: String,
+    content: String,
+    #[serde(default = "default_author_name")]
+    author_name: String,
+    #[serde(default = "default_read_author_name")]
+    read_author_name: bool,
+    #[serde(default)]
+    mentions: Mentions,
+    #[serde(default)]
+    dict: HashMap<String, String>,
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+    /// カンマ区切り。省略した場合はデフォルトの順序とする
+    filters: Option<String>,
+    expected: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Mentions {
+    #[serde(default)]
+    users: HashMap<u64, String>,
+    #[serde(default)]
+    roles: HashMap<u64, String>,
+    #[serde(default)]
+    channels: HashMap<u64, String>,
+}
+
+fn default_author_name() -> String {
+    "たろう".to_string()
+}
+
+const fn default_read_author_name() -> bool {
+    true
+}
+
+const fn default_max_length() -> usize {
+    MAX_LENGTH
+}
+
+impl Case {
+    fn input(&self) -> ReadInput {
+        ReadInput {
+            content: self.content.clone(),
+            author_name: self.author_name.clone(),
+            read_author_name: self.read_author_name,
+            mentions: MentionNames {
+                users: self.mentions.users.clone(),
+                roles: self.mentions.roles.clone(),
+                channels: self.mentions.channels.clone(),
+            },
+            dict: self.dict.clone(),
+            max_length: self.max_length,
+            filters: self.filters.as_deref().map_or_else(
+                || ReadFilter::DEFAULT_ORDER.to_vec(),
+                |filters| ReadFilter::split(filters).unwrap(),
+            ),
+        }
+    }
+}
+
+#[test]
+fn golden() {
+    let cases: Vec<Case> = serde_yaml::from_str(include_str!("testdata/golden.yaml")).unwrap();
+
+    let failures = cases
+        .iter()
+        .filter_map(|case| {
+            let actual = build_read_text(&case.input()).unwrap();
+            (actual != case.expected).then(|| {
+                format!(
+                    "{}\n  expected: {:?}\n  actual:   {:?}",
+                    case.name, case.expected, actual
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn mentions_are_parsed() {
+    let mentions = parse_mentions("<@1> <@!2> <@&3> <#4> <@0> <:emoji:5> <@x>").collect::<Vec<_>>();
+
+    assert_eq!(
+        mentions,
+        vec![
+            Mention::User(1),
+            Mention::User(2),
+            Mention::Role(3),
+            Mention::Channel(4),
+        ]
+    );
+}
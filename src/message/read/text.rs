@@ -0,0 +1,93 @@
+//! 読み上げる文章を組み立てる処理
+//!
+//! Discordやデータベースへの問い合わせは行わず、[`ReadInput`]のみから文章を組み立てる。
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+
+use super::filter;
+use crate::db::setting::ReadFilter;
+
+/// 読み上げる文章の最大の文字数
+pub const MAX_LENGTH: usize = 60;
+
+/// 読み上げる文章を組み立てるために必要な情報
+#[derive(Debug, Clone)]
+pub struct ReadInput {
+    /// メッセージの内容
+    pub content: String,
+    /// 送信者の名前。読み方が設定されている場合はその読み方
+    pub author_name: String,
+    /// 送信者の名前を読み上げるか
+    pub read_author_name: bool,
+    /// メンションされたユーザー、ロール、チャンネルの名前
+    pub mentions: MentionNames,
+    /// 読み替える語句とその読み方。ユーザー、サーバー、全体の辞書を優先順位に従って統合したもの
+    pub dict: HashMap<String, String>,
+    /// 読み上げる文章の最大の文字数
+    pub max_length: usize,
+    /// メッセージの内容に適用する処理。この順に適用する
+    pub filters: Vec<ReadFilter>,
+}
+
+/// メンションの対象のIDと名前の対応
+#[derive(Debug, Clone, Default)]
+pub struct MentionNames {
+    /// ユーザーの名前。読み方が設定されている場合はその読み方
+    pub users: HashMap<u64, String>,
+    pub roles: HashMap<u64, String>,
+    pub channels: HashMap<u64, String>,
+}
+
+/// メッセージに含まれるメンション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mention {
+    User(u64),
+    Role(u64),
+    Channel(u64),
+}
+
+/// 読み上げる文章を組み立てる。メッセージの内容に`input.filters`を順に適用する。
+pub fn build_read_text(input: &ReadInput) -> Result<String> {
+    input
+        .filters
+        .iter()
+        .try_fold(input.content.clone(), |text, &kind| {
+            filter::get(kind).apply(text, input)
+        })
+}
+
+/// メッセージに含まれるメンションを返す
+pub fn parse_mentions(content: &str) -> impl Iterator<Item = Mention> + '_ {
+    mention_regex()
+        .captures_iter(content)
+        .filter_map(|caps| parse_mention(&caps))
+}
+
+pub(super) fn parse_mention(caps: &Captures) -> Option<Mention> {
+    // u64に収まらないIDや0はメンションとして扱わない
+    let id = caps[2].parse().ok().filter(|&id| id != 0)?;
+
+    let mention = match &caps[1] {
+        "#" => Mention::Channel(id),
+        "@&" => Mention::Role(id),
+        _ => Mention::User(id),
+    };
+    Some(mention)
+}
+
+macro_rules! regex {
+    ($re:literal $(,)?) => {{
+        static RE: std::sync::LazyLock<regex::Regex> =
+            std::sync::LazyLock::new(|| regex::Regex::new($re).unwrap());
+        &RE
+    }};
+}
+
+pub(super) use regex;
+
+pub(super) fn mention_regex() -> &'static Regex {
+    regex!(r"<(@!?|@&|#)(\d+)>")
+}
@@ -0,0 +1,249 @@
+use std::{sync::LazyLock, time::Duration};
+
+use anyhow::Result;
+use prometheus_client::{
+    encoding::{EncodeLabelSet, text::encode},
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{Histogram, exponential_buckets},
+    },
+    registry::Registry,
+};
+
+/// The reason why a message in a bound text channel was not read aloud
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Sent by Koe itself
+    OwnMessage,
+    /// Starts with a semicolon
+    CommandPrefix,
+    /// Matched the ignore list, the opt-out or the `vc_only` setting
+    Ignored,
+    /// Koe left the voice channel before reading the message
+    Disconnected,
+    /// Nothing was left to read after building the text
+    Empty,
+    /// The queue of the guild was full
+    QueueFull,
+    /// Building the text or synthesizing the speech failed
+    Failed,
+    /// Koe was shutting down
+    ShuttingDown,
+}
+
+impl SkipReason {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::OwnMessage => "own_message",
+            Self::CommandPrefix => "command_prefix",
+            Self::Ignored => "ignored",
+            Self::Disconnected => "disconnected",
+            Self::Empty => "empty",
+            Self::QueueFull => "queue_full",
+            Self::Failed => "failed",
+            Self::ShuttingDown => "shutting_down",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ReasonLabels {
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct EngineLabels {
+    engine: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct GuildLabels {
+    guild_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct OperationLabels {
+    operation: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct CommandLabels {
+    command: String,
+}
+
+type HistogramFamily<L> = Family<L, Histogram, fn() -> Histogram>;
+
+struct Metrics {
+    registry: Registry,
+    messages_seen: Counter,
+    messages_read: Counter,
+    messages_skipped: Family<ReasonLabels, Counter>,
+    synthesis_duration: HistogramFamily<EngineLabels>,
+    synthesis_errors: Family<EngineLabels, Counter>,
+    queue_length: Family<GuildLabels, Gauge>,
+    redis_duration: HistogramFamily<OperationLabels>,
+    connected_guilds: Gauge,
+    commands: Family<CommandLabels, Counter>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("koe");
+
+        let messages_seen = Counter::default();
+        registry.register(
+            "messages_seen",
+            "Messages received in the text channels bound to a voice channel",
+            messages_seen.clone(),
+        );
+
+        let messages_read = Counter::default();
+        registry.register(
+            "messages_read",
+            "Messages queued to be read aloud",
+            messages_read.clone(),
+        );
+
+        let messages_skipped = Family::default();
+        registry.register(
+            "messages_skipped",
+            "Messages not read aloud, by reason",
+            messages_skipped.clone(),
+        );
+
+        let synthesis_duration: HistogramFamily<_> =
+            Family::new_with_constructor(|| Histogram::new(exponential_buckets(0.05, 2.0, 10)));
+        registry.register(
+            "synthesis_duration_seconds",
+            "Time taken to synthesize a speech, by VOICEVOX ENGINE",
+            synthesis_duration.clone(),
+        );
+
+        let synthesis_errors = Family::default();
+        registry.register(
+            "synthesis_errors",
+            "Failed speech syntheses, by VOICEVOX ENGINE",
+            synthesis_errors.clone(),
+        );
+
+        let queue_length = Family::default();
+        registry.register(
+            "queue_length",
+            "Tracks in the queue, including the one being played, by guild",
+            queue_length.clone(),
+        );
+
+        let redis_duration: HistogramFamily<_> =
+            Family::new_with_constructor(|| Histogram::new(exponential_buckets(0.0005, 2.0, 12)));
+        registry.register(
+            "redis_duration_seconds",
+            "Time taken by Redis storage operations, by operation",
+            redis_duration.clone(),
+        );
+
+        let connected_guilds = Gauge::default();
+        registry.register(
+            "connected_guilds",
+            "Guilds where Koe is connected to a voice channel",
+            connected_guilds.clone(),
+        );
+
+        let commands = Family::default();
+        registry.register(
+            "commands",
+            "Slash command invocations, by command",
+            commands.clone(),
+        );
+
+        Self {
+            registry,
+            messages_seen,
+            messages_read,
+            messages_skipped,
+            synthesis_duration,
+            synthesis_errors,
+            queue_length,
+            redis_duration,
+            connected_guilds,
+            commands,
+        }
+    }
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub fn message_seen() {
+    METRICS.messages_seen.inc();
+}
+
+pub fn message_read() {
+    METRICS.messages_read.inc();
+}
+
+pub fn message_skipped(reason: SkipReason) {
+    METRICS
+        .messages_skipped
+        .get_or_create(&ReasonLabels {
+            reason: reason.as_str(),
+        })
+        .inc();
+}
+
+/// Records a speech synthesis on the VOICEVOX ENGINE at `engine`.
+pub fn observe_synthesis(engine: &str, duration: Duration, succeeded: bool) {
+    let labels = EngineLabels {
+        engine: engine.to_string(),
+    };
+
+    if succeeded {
+        METRICS
+            .synthesis_duration
+            .get_or_create(&labels)
+            .observe(duration.as_secs_f64());
+    } else {
+        METRICS.synthesis_errors.get_or_create(&labels).inc();
+    }
+}
+
+pub fn observe_redis(operation: &'static str, duration: Duration) {
+    METRICS
+        .redis_duration
+        .get_or_create(&OperationLabels { operation })
+        .observe(duration.as_secs_f64());
+}
+
+pub fn command_invoked(command: &str) {
+    METRICS
+        .commands
+        .get_or_create(&CommandLabels {
+            command: command.to_string(),
+        })
+        .inc();
+}
+
+/// Replaces the queue lengths of all guilds, so that guilds Koe has left are no longer reported.
+pub fn set_queue_lengths(lengths: impl IntoIterator<Item = (u64, usize)>) {
+    METRICS.queue_length.clear();
+
+    for (guild_id, length) in lengths {
+        METRICS
+            .queue_length
+            .get_or_create(&GuildLabels { guild_id })
+            .set(i64::try_from(length).unwrap_or(i64::MAX));
+    }
+}
+
+pub fn set_connected_guilds(count: usize) {
+    METRICS
+        .connected_guilds
+        .set(i64::try_from(count).unwrap_or(i64::MAX));
+}
+
+/// Returns all metrics in the text format of `OpenMetrics`.
+pub fn encode_text() -> Result<String> {
+    let mut buffer = String::new();
+    encode(&mut buffer, &METRICS.registry)?;
+    Ok(buffer)
+}
@@ -0,0 +1,191 @@
+use std::{
+    ffi::OsStr,
+    path::Path,
+    sync::{Arc, PoisonError},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::mpsc,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    app_state::AppState,
+    config::{self, Config, VoicevoxConfig},
+    tts::voicevox::VoicevoxClient,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How long to wait for further changes after the config file changes, since editors and
+/// Kubernetes replace a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The entry that Kubernetes swaps when it updates a mounted `ConfigMap` or Secret
+const KUBERNETES_DATA_DIR: &str = "..data";
+
+/// Spawns a task that reloads the config whenever Koe receives SIGHUP or the config file changes.
+///
+/// Only the settings that are safe to replace at runtime are applied. Changes to the others are
+/// logged and take effect on the next restart.
+pub fn spawn(state: Arc<AppState>, config: Config) -> Result<()> {
+    let mut hangup = signal(SignalKind::hangup()).context("Failed to listen for SIGHUP")?;
+
+    let (changed_sender, mut changed) = mpsc::channel(1);
+    let watcher = config::file_path().and_then(|path| {
+        watch(&path, changed_sender)
+            .with_context(|| {
+                format!(
+                    "Failed to watch config file {}; reload it with SIGHUP instead",
+                    path.display()
+                )
+            })
+            .inspect_err(|err| warn!("{err:?}"))
+            .ok()
+    });
+
+    tokio::spawn(async move {
+        // Dropping the watcher stops watching
+        let _watcher = watcher;
+        let mut current = config;
+
+        loop {
+            tokio::select! {
+                received = hangup.recv() => {
+                    if received.is_none() {
+                        break;
+                    }
+                    info!("Received SIGHUP, reloading config...");
+                }
+                Some(()) = changed.recv() => {
+                    tokio::time::sleep(DEBOUNCE).await;
+                    while changed.try_recv().is_ok() {}
+                    info!("Config file changed, reloading config...");
+                }
+            }
+
+            if let Err(err) = reload(&state, &mut current)
+                .await
+                .context("Failed to reload config; keeping the current config")
+            {
+                error!("{err:?}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Notifies `changed` when the file at `path` is modified, created, replaced or removed.
+///
+/// The directory is watched instead of the file itself so that a file replaced by renaming, as
+/// editors and Kubernetes do, keeps being watched.
+fn watch(path: &Path, changed: mpsc::Sender<()>) -> Result<RecommendedWatcher> {
+    let file_name = path
+        .file_name()
+        .context("Config file path has no file name")?
+        .to_os_string();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                warn!("Error while watching config file: {err}");
+                return;
+            }
+        };
+
+        // Reading the file on reload generates access events, which must not trigger a reload
+        if event.kind.is_access() {
+            return;
+        }
+
+        let is_config_file = event.paths.iter().any(|path| {
+            path.file_name()
+                .is_some_and(|name| name == file_name || name == OsStr::new(KUBERNETES_DATA_DIR))
+        });
+        if is_config_file {
+            // A reload is already pending if the channel is full
+            let _ = changed.try_send(());
+        }
+    })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    Ok(watcher)
+}
+
+async fn reload(state: &AppState, current: &mut Config) -> Result<()> {
+    let new = config::load().await?;
+
+    let changes = current.changes(&new);
+    if changes.is_empty() {
+        info!("Config reloaded; nothing changed");
+        return Ok(());
+    }
+
+    // Make sure the new endpoint works before switching to it so that a typo does not silence Koe
+    let voicevox_client = if client_options(&new.voicevox) == client_options(&current.voicevox) {
+        None
+    } else {
+        let client = Arc::new(VoicevoxClient::new(&new.voicevox)?);
+        client.presets().await.with_context(|| {
+            format!(
+                "Failed to connect to the new VOICEVOX ENGINE at {}",
+                new.voicevox.api_base
+            )
+        })?;
+        Some(client)
+    };
+
+    {
+        let mut reloadable = state
+            .reloadable
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = &voicevox_client {
+            reloadable.voicevox_client = Arc::clone(client);
+            // The speakers of the new engine have not been initialized yet
+            state.warmed_up.send_replace(false);
+        }
+        reloadable.global_dict = Arc::new(new.dict.global.clone());
+        reloadable.wait_for_warmup = new.voicevox.wait_for_warmup;
+        reloadable.catchup = new.catchup;
+    }
+    // Let a reload also pick up presets edited on the engine
+    if voicevox_client.is_none() {
+        state.voicevox_client().invalidate_presets();
+    }
+    current.apply_reloadable(&new);
+
+    for change in changes {
+        if change.reloadable {
+            info!("Config `{}` changed and has been applied", change.key);
+        } else {
+            warn!(
+                "Config `{}` changed but requires a restart to take effect",
+                change.key
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The settings that require building a new [`VoicevoxClient`] when changed
+fn client_options(config: &VoicevoxConfig) -> (&str, u64, u64, u32) {
+    (
+        &config.api_base,
+        config.connect_timeout_ms,
+        config.request_timeout_ms,
+        config.max_retries,
+    )
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::watch;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn changes_to_config_file_are_notified() {
+    let dir = std::env::temp_dir().join(format!("koe-test-{}", rand::random::<u64>()));
+    std::fs::create_dir(&dir).unwrap();
+    let path = dir.join("koe.yaml");
+    std::fs::write(&path, "a: 1\n").unwrap();
+
+    let (sender, mut changed) = mpsc::channel(1);
+    let _watcher = watch(&path, sender).unwrap();
+
+    // 他のファイルの変更は無視する
+    std::fs::write(dir.join("other.yaml"), "b: 2\n").unwrap();
+    std::fs::read_to_string(&path).unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), changed.recv())
+            .await
+            .is_err()
+    );
+
+    // エディタと同様に、別のファイルに書き込んでから置き換える
+    let temp = dir.join("koe.yaml.tmp");
+    std::fs::write(&temp, "a: 2\n").unwrap();
+    std::fs::rename(&temp, &path).unwrap();
+    tokio::time::timeout(TIMEOUT, changed.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
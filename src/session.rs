@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, GuildId},
+};
+use songbird::Songbird;
+use tracing::{debug, info, warn};
+
+use crate::{
+    app_state::{self, AppState, ConnectedGuildState},
+    db::session::Session,
+    message, voice_call, voice_state,
+};
+
+/// Saves the voice channels Koe is connected to, so that it can rejoin them after restarting.
+pub async fn save(state: &AppState, songbird: &Songbird) -> Result<()> {
+    let guild_states = state
+        .connected_guild_states
+        .iter()
+        .map(|entry| (*entry.key(), entry.value().bound_text_channel))
+        .collect::<Vec<_>>();
+
+    let mut sessions = Vec::with_capacity(guild_states.len());
+    for (guild_id, text_channel_id) in guild_states {
+        if let Some(voice_channel_id) = voice_call::current_channel(songbird, guild_id).await {
+            sessions.push(Session {
+                guild_id: guild_id.get(),
+                voice_channel_id: voice_channel_id.0.get(),
+                text_channel_id: text_channel_id.get(),
+            });
+        }
+    }
+
+    let count = sessions.len();
+    state.storage.save_sessions(sessions).await?;
+    info!("Saved {count} voice sessions");
+
+    Ok(())
+}
+
+/// Rejoins the voice channels saved by [`save`] where someone is still listening.
+///
+/// The saved sessions are removed, so that they are restored at most once.
+pub async fn restore(ctx: &Context) -> Result<()> {
+    let state = app_state::get(ctx).await?;
+    let sessions = state.storage.take_sessions().await?;
+
+    for session in sessions {
+        if let Err(err) = restore_session(ctx, &state, &session)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to rejoin voice channel {} in guild {}",
+                    session.voice_channel_id, session.guild_id
+                )
+            })
+        {
+            warn!("{err:?}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn restore_session(ctx: &Context, state: &AppState, session: &Session) -> Result<()> {
+    let guild_id = GuildId::new(session.guild_id);
+    let voice_channel_id = ChannelId::new(session.voice_channel_id);
+
+    if !voice_state::has_listeners(ctx, guild_id, voice_channel_id)? {
+        debug!(
+            "Not rejoining voice channel {voice_channel_id} in guild {guild_id} since no one is \
+             there"
+        );
+        return Ok(());
+    }
+
+    voice_call::join_deaf(ctx, guild_id, voice_channel_id).await?;
+
+    state.connected_guild_states.insert(
+        guild_id,
+        Arc::new(ConnectedGuildState {
+            bound_text_channel: ChannelId::new(session.text_channel_id),
+            message_sender: message::spawn_reader(ctx.clone(), state, guild_id),
+        }),
+    );
+
+    info!("Rejoined voice channel {voice_channel_id} in guild {guild_id}");
+    Ok(())
+}
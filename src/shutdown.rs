@@ -0,0 +1,160 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use serenity::{cache::Cache, gateway::ShardManager, model::id::GuildId};
+use songbird::Songbird;
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    time::timeout,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    app_state::{AppState, ConnectedGuildState},
+    config::{InFlightPolicy, ShutdownConfig},
+    session,
+    tts::speech::{SpeechRequest, list_preset_ids, make_speech},
+    voice_call::{self, TrackMetadata},
+};
+
+/// The text read aloud when [`InFlightPolicy::Announce`] is configured
+const ANNOUNCEMENT: &str = "読み上げを終了します。";
+
+/// Spawns a task that shuts Koe down gracefully when it receives SIGTERM or SIGINT.
+///
+/// Koe stops accepting new messages, handles the messages being read according to
+/// [`ShutdownConfig::in_flight`] within [`ShutdownConfig::timeout_secs`], saves the voice sessions,
+/// leaves the voice channels and finally shuts down the shards, which makes `Client::start` return.
+pub fn spawn(
+    state: Arc<AppState>,
+    songbird: Arc<Songbird>,
+    shard_manager: Arc<ShardManager>,
+    cache: Arc<Cache>,
+    config: ShutdownConfig,
+) -> Result<()> {
+    let mut terminate = signal(SignalKind::terminate()).context("Failed to listen for SIGTERM")?;
+    let mut interrupt = signal(SignalKind::interrupt()).context("Failed to listen for SIGINT")?;
+
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = terminate.recv() => info!("Received SIGTERM, shutting down..."),
+            _ = interrupt.recv() => info!("Received SIGINT, shutting down..."),
+        }
+
+        state.shutdown.send_replace(Some(config.in_flight));
+
+        // Save the sessions before anything else so that they are restored even if Koe is killed
+        // while waiting for the messages to be read
+        if let Err(err) = session::save(&state, &songbird)
+            .await
+            .context("Failed to save voice sessions")
+        {
+            error!("{err:?}");
+        }
+
+        let guild_states = state
+            .connected_guild_states
+            .iter()
+            .map(|entry| (*entry.key(), Arc::clone(entry.value())))
+            .collect::<Vec<_>>();
+
+        if timeout(
+            Duration::from_secs(config.timeout_secs),
+            finish_reading(&state, &songbird, &cache, &guild_states, config.in_flight),
+        )
+        .await
+        .is_err()
+        {
+            warn!(
+                "Messages were still being read after {} seconds; leaving anyway",
+                config.timeout_secs
+            );
+        }
+
+        for (guild_id, _) in guild_states {
+            if let Err(err) = voice_call::disconnect(&songbird, guild_id)
+                .await
+                .with_context(|| format!("Failed to leave voice channel in guild {guild_id}"))
+            {
+                error!("{err:?}");
+            }
+        }
+        state.connected_guild_states.clear();
+
+        shard_manager.shutdown_all().await;
+    });
+
+    Ok(())
+}
+
+async fn finish_reading(
+    state: &AppState,
+    songbird: &Songbird,
+    cache: &Cache,
+    guild_states: &[(GuildId, Arc<ConnectedGuildState>)],
+    policy: InFlightPolicy,
+) {
+    // The reader tasks stop once they have read or dropped the messages sent before shutting down
+    for (_, guild_state) in guild_states {
+        guild_state.message_sender.closed().await;
+    }
+
+    match policy {
+        InFlightPolicy::Finish => {
+            for (guild_id, _) in guild_states {
+                voice_call::wait_until_played(songbird, *guild_id).await;
+            }
+        }
+        InFlightPolicy::Announce => {
+            let audio = match make_announcement(state).await {
+                Ok(audio) => audio,
+                Err(err) => {
+                    error!(
+                        "{:?}",
+                        err.context("Failed to synthesize shutdown announcement")
+                    );
+                    return;
+                }
+            };
+
+            for (guild_id, _) in guild_states {
+                let metadata = TrackMetadata {
+                    author_id: cache.current_user().id,
+                    author_name: cache.current_user().name.clone(),
+                    text: ANNOUNCEMENT.to_string(),
+                    summarized_count: None,
+                };
+                if let Err(err) =
+                    voice_call::replace_queue(songbird, *guild_id, audio.clone(), metadata)
+                        .await
+                        .with_context(|| format!("Failed to announce shutdown in guild {guild_id}"))
+                {
+                    error!("{err:?}");
+                }
+            }
+            for (guild_id, _) in guild_states {
+                voice_call::wait_until_played(songbird, *guild_id).await;
+            }
+        }
+        // Leaving the voice channels stops the tracks
+        InFlightPolicy::Discard => {}
+    }
+}
+
+async fn make_announcement(state: &AppState) -> Result<Vec<u8>> {
+    let client = state.voicevox_client();
+    let preset_id = *list_preset_ids(&client)
+        .await?
+        .first()
+        .context("No presets available")?;
+
+    make_speech(
+        &client,
+        SpeechRequest {
+            text: ANNOUNCEMENT.to_string(),
+            preset_id,
+            speed_scale: 1.0,
+        },
+    )
+    .await
+}
@@ -0,0 +1,243 @@
+//! テスト用のVOICEVOX ENGINEのモック
+//!
+//! 実際のエンジンと同じエンドポイントを提供し、エラーや遅延を注入できる。
+
+use std::time::Duration;
+
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use super::voicevox::VoicevoxClient;
+use crate::config::VoicevoxConfig;
+
+/// モックが提供するプリセット
+/// (プリセットのID, スタイルのID, 話速)
+pub const PRESETS: &[(i64, i64, f64)] = &[(1, 10, 1.0), (2, 20, 1.5)];
+
+/// モックが返すエンジンのバージョン
+pub const VERSION: &str = "0.24.1";
+
+/// エラーや遅延の注入に使うエンドポイント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Presets,
+    AudioQueryFromPreset,
+    Synthesis,
+    InitializeSpeaker,
+    IsInitializedSpeaker,
+    Version,
+}
+
+impl Endpoint {
+    const ALL: [Self; 6] = [
+        Self::Presets,
+        Self::AudioQueryFromPreset,
+        Self::Synthesis,
+        Self::InitializeSpeaker,
+        Self::IsInitializedSpeaker,
+        Self::Version,
+    ];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Presets => "/presets",
+            Self::AudioQueryFromPreset => "/audio_query_from_preset",
+            Self::Synthesis => "/synthesis",
+            Self::InitializeSpeaker => "/initialize_speaker",
+            Self::IsInitializedSpeaker => "/is_initialized_speaker",
+            Self::Version => "/version",
+        }
+    }
+
+    fn method(self) -> &'static str {
+        match self {
+            Self::Presets | Self::IsInitializedSpeaker | Self::Version => "GET",
+            Self::AudioQueryFromPreset | Self::Synthesis | Self::InitializeSpeaker => "POST",
+        }
+    }
+
+    fn respond(self, request: &Request) -> ResponseTemplate {
+        match self {
+            Self::Presets => ResponseTemplate::new(200).set_body_json(presets_json()),
+            Self::AudioQueryFromPreset => respond_audio_query(request),
+            Self::Synthesis => ResponseTemplate::new(200).set_body_raw(tiny_wav(), "audio/wav"),
+            Self::InitializeSpeaker => ResponseTemplate::new(204),
+            Self::IsInitializedSpeaker => ResponseTemplate::new(200).set_body_json(true),
+            Self::Version => ResponseTemplate::new(200).set_body_json(VERSION),
+        }
+    }
+}
+
+pub struct MockVoicevox {
+    server: MockServer,
+}
+
+impl MockVoicevox {
+    /// すべてのエンドポイントが正常に応答するモックを起動する
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+
+        for endpoint in Endpoint::ALL {
+            Mock::given(method(endpoint.method()))
+                .and(path(endpoint.path()))
+                .respond_with(move |request: &Request| endpoint.respond(request))
+                .mount(&server)
+                .await;
+        }
+
+        Self { server }
+    }
+
+    pub fn client(&self) -> VoicevoxClient {
+        Self::client_with(&self.config())
+    }
+
+    /// 設定を変更してクライアントを作る場合に使う、モックに接続する設定
+    pub fn config(&self) -> VoicevoxConfig {
+        VoicevoxConfig {
+            api_base: self.server.uri(),
+            wait_for_warmup: false,
+            connect_timeout_ms: 1000,
+            request_timeout_ms: 5000,
+            max_retries: 2,
+        }
+    }
+
+    pub fn client_with(config: &VoicevoxConfig) -> VoicevoxClient {
+        // main関数と同様にプロバイダを設定する。他のテストで設定済みの場合は失敗するが問題ない
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        VoicevoxClient::new(config).unwrap()
+    }
+
+    /// `endpoint`への次の`times`回のリクエストに`status`で応答する
+    pub async fn fail(&self, endpoint: Endpoint, status: u16, times: u64) {
+        self.respond(endpoint, ResponseTemplate::new(status), times)
+            .await;
+    }
+
+    /// `endpoint`への次の`times`回のリクエストに`response`で応答する
+    pub async fn respond(&self, endpoint: Endpoint, response: ResponseTemplate, times: u64) {
+        Mock::given(method(endpoint.method()))
+            .and(path(endpoint.path()))
+            .respond_with(response)
+            .up_to_n_times(times)
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// `endpoint`へのリクエストに`delay`だけ遅れて応答する
+    pub async fn delay(&self, endpoint: Endpoint, delay: Duration) {
+        Mock::given(method(endpoint.method()))
+            .and(path(endpoint.path()))
+            .respond_with(Delayed { endpoint, delay })
+            .with_priority(2)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// `endpoint`が受け取ったリクエストを古い順に返す
+    pub async fn requests(&self, endpoint: Endpoint) -> Vec<Request> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|request| request.url.path() == endpoint.path())
+            .collect()
+    }
+}
+
+struct Delayed {
+    endpoint: Endpoint,
+    delay: Duration,
+}
+
+impl Respond for Delayed {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        self.endpoint.respond(request).set_delay(self.delay)
+    }
+}
+
+/// リクエストのクエリパラメータの値を返す
+pub fn query_param(request: &Request, name: &str) -> Option<String> {
+    request
+        .url
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn presets_json() -> serde_json::Value {
+    PRESETS
+        .iter()
+        .map(|(id, style_id, speed_scale)| {
+            json!({
+                "id": id,
+                "name": format!("プリセット{id}"),
+                "speaker_uuid": "00000000-0000-0000-0000-000000000000",
+                "style_id": style_id,
+                "speedScale": speed_scale,
+                "pitchScale": 0.0,
+                "intonationScale": 1.0,
+                "volumeScale": 1.0,
+                "prePhonemeLength": 0.1,
+                "postPhonemeLength": 0.1,
+            })
+        })
+        .collect()
+}
+
+/// プリセットの話速を反映し、読み上げる文章を`kana`に含めたクエリを返す
+fn respond_audio_query(request: &Request) -> ResponseTemplate {
+    let preset = query_param(request, "preset_id")
+        .and_then(|id| id.parse::<i64>().ok())
+        .and_then(|id| PRESETS.iter().find(|(preset_id, ..)| *preset_id == id));
+    let Some((_, _, speed_scale)) = preset else {
+        // 実際のエンジンと同様に、存在しないプリセットは文字列の`detail`で報告する
+        return ResponseTemplate::new(422)
+            .set_body_json(json!({ "detail": "該当するプリセットIDが見つかりません" }));
+    };
+
+    ResponseTemplate::new(200).set_body_json(json!({
+        "accent_phrases": [],
+        "speedScale": speed_scale,
+        "pitchScale": 0.0,
+        "intonationScale": 1.0,
+        "volumeScale": 1.0,
+        "prePhonemeLength": 0.1,
+        "postPhonemeLength": 0.1,
+        "outputSamplingRate": 24000,
+        "outputStereo": false,
+        "kana": query_param(request, "text").unwrap_or_default(),
+    }))
+}
+
+/// 無音のサンプルを1つだけ含むWAVを返す
+pub fn tiny_wav() -> Vec<u8> {
+    const SAMPLE_RATE: u32 = 24000;
+    const DATA: [u8; 2] = [0, 0];
+    let data_len = u32::try_from(DATA.len()).unwrap();
+
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM、モノラル
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // バイトレート、ブロックサイズ、量子化ビット数
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    wav.extend_from_slice(&DATA);
+    wav
+}
@@ -1,2 +1,6 @@
+#[cfg(test)]
+pub mod mock;
 pub mod speech;
+#[cfg(test)]
+mod tests;
 pub mod voicevox;
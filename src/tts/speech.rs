@@ -1,16 +1,61 @@
-use anyhow::{Context, Result};
+use std::time::Instant;
 
-use super::voicevox::{GenerateQueryFromPresetParams, Preset, SynthesisParams, VoicevoxClient};
+use anyhow::{Context, Result};
+use futures::{StreamExt as _, stream};
+use tracing::{info, instrument};
+
+use super::voicevox::{
+    GenerateQueryFromPresetParams, Preset, SynthesisParams, VoicevoxClient, VoicevoxError,
+};
+use crate::metrics;
+
+/// The number of speakers initialized at the same time
+const INITIALIZE_CONCURRENCY: usize = 4;
+
+/// Initializes the styles used by the presets so that the first speech is not delayed.
+/// Returns the initialized style IDs.
+pub async fn initialize_speakers(client: &VoicevoxClient) -> Result<Vec<i64>> {
+    let mut style_ids = client
+        .presets()
+        .await?
+        .into_iter()
+        .map(|preset| preset.style_id)
+        .collect::<Vec<_>>();
+    style_ids.sort_unstable();
+    style_ids.dedup();
+
+    let total = style_ids.len();
+    info!("Initializing {total} speakers...");
+
+    let mut initializations = stream::iter(style_ids.clone())
+        .map(|style_id| async move {
+            client
+                .initialize_speaker(style_id, true)
+                .await
+                .with_context(|| format!("Failed to initialize speaker {style_id}"))
+                .map(|()| style_id)
+        })
+        .buffer_unordered(INITIALIZE_CONCURRENCY);
 
-pub async fn initialize_speakers(client: &VoicevoxClient) -> Result<()> {
-    let preset_list = client.presets().await?;
-    for preset in preset_list {
-        client.initialize_speaker(preset.style_id, true).await?;
+    let mut done = 0;
+    while let Some(style_id) = initializations.next().await.transpose()? {
+        done += 1;
+        info!("Initialized speaker {style_id} ({done}/{total})");
     }
-    Ok(())
+
+    Ok(style_ids)
 }
 
+#[instrument(name = "synthesis", level = "debug", skip_all)]
 pub async fn make_speech(client: &VoicevoxClient, option: SpeechRequest) -> Result<Vec<u8>> {
+    let started_at = Instant::now();
+    let result = synthesize(client, option).await;
+    metrics::observe_synthesis(client.api_base(), started_at.elapsed(), result.is_ok());
+
+    result
+}
+
+async fn synthesize(client: &VoicevoxClient, option: SpeechRequest) -> Result<Vec<u8>> {
     let preset = get_preset(client, option.preset_id).await?;
 
     let query = client
@@ -19,6 +64,7 @@ pub async fn make_speech(client: &VoicevoxClient, option: SpeechRequest) -> Resu
             text: option.text,
         })
         .await?;
+    let query = scale_speed(&query, option.speed_scale)?;
 
     let audio = client
         .synthesis(SynthesisParams {
@@ -31,26 +77,49 @@ pub async fn make_speech(client: &VoicevoxClient, option: SpeechRequest) -> Resu
 }
 
 pub async fn list_preset_ids(client: &VoicevoxClient) -> Result<Vec<PresetId>> {
-    let preset_list = client.presets().await?;
-    let ids = preset_list.into_iter().map(|p| PresetId(p.id)).collect();
+    let preset_list = client.cached_presets().await?;
+    let ids = preset_list.iter().map(|p| PresetId(p.id)).collect();
     Ok(ids)
 }
 
 async fn get_preset(client: &VoicevoxClient, id: PresetId) -> Result<Preset> {
-    let preset_list = client.presets().await?;
+    let find = |preset_list: &[Preset]| preset_list.iter().find(|p| PresetId(p.id) == id).cloned();
 
-    let preset = preset_list
-        .into_iter()
-        .find(|p| PresetId(p.id) == id)
-        .with_context(|| format!("Preset {} is not available", id.0))?;
+    if let Some(preset) = find(&client.cached_presets().await?) {
+        return Ok(preset);
+    }
+
+    // The presets may have been added to the engine after they were cached
+    let preset = find(&client.presets().await?).ok_or(VoicevoxError::InvalidPreset(id.0))?;
 
     Ok(preset)
 }
 
+/// プリセットの話速に`scale`を掛ける
+fn scale_speed(query: &str, scale: f64) -> Result<String> {
+    if (scale - 1.0).abs() < f64::EPSILON {
+        return Ok(query.to_string());
+    }
+
+    let mut query: serde_json::Value =
+        serde_json::from_str(query).context("Failed to parse audio query")?;
+    let speed_scale = query
+        .get_mut("speedScale")
+        .context("speedScale is missing in audio query")?;
+    let current = speed_scale
+        .as_f64()
+        .context("speedScale in audio query is not a number")?;
+    *speed_scale = (current * scale).into();
+
+    Ok(query.to_string())
+}
+
 #[derive(Debug, Clone)]
 pub struct SpeechRequest {
     pub text: String,
     pub preset_id: PresetId,
+    /// プリセットの話速に対する倍率
+    pub speed_scale: f64,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -0,0 +1,333 @@
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use wiremock::ResponseTemplate;
+
+use super::{
+    mock::{Endpoint, MockVoicevox, PRESETS, VERSION, query_param, tiny_wav},
+    speech::{PresetId, SpeechRequest, initialize_speakers, list_preset_ids, make_speech},
+    voicevox::{GenerateQueryFromPresetParams, VoicevoxError},
+};
+use crate::config::VoicevoxConfig;
+
+fn speech_request(preset_id: i64, speed_scale: f64) -> SpeechRequest {
+    SpeechRequest {
+        text: "こんにちは".to_string(),
+        preset_id: PresetId(preset_id),
+        speed_scale,
+    }
+}
+
+#[tokio::test]
+async fn presets_are_listed() {
+    let mock = MockVoicevox::start().await;
+
+    let presets = mock.client().presets().await.unwrap();
+    let ids = list_preset_ids(&mock.client()).await.unwrap();
+
+    assert_eq!(presets.len(), PRESETS.len());
+    assert_eq!(presets[1].style_id, 20);
+    assert!((presets[1].speed_scale - 1.5).abs() < f64::EPSILON);
+    assert_eq!(ids, vec![PresetId(1), PresetId(2)]);
+}
+
+#[tokio::test]
+async fn version_is_returned() {
+    let mock = MockVoicevox::start().await;
+
+    let version = mock.client().version().await.unwrap();
+
+    assert_eq!(version, VERSION);
+}
+
+#[tokio::test]
+async fn make_speech_synthesizes_with_preset_style() {
+    let mock = MockVoicevox::start().await;
+
+    let audio = make_speech(&mock.client(), speech_request(2, 1.0))
+        .await
+        .unwrap();
+    assert_eq!(audio, tiny_wav());
+
+    let queries = mock.requests(Endpoint::AudioQueryFromPreset).await;
+    assert_eq!(queries.len(), 1);
+    assert_eq!(query_param(&queries[0], "preset_id").as_deref(), Some("2"));
+    assert_eq!(
+        query_param(&queries[0], "text").as_deref(),
+        Some("こんにちは")
+    );
+
+    let synthesis = mock.requests(Endpoint::Synthesis).await;
+    assert_eq!(synthesis.len(), 1);
+    assert_eq!(query_param(&synthesis[0], "speaker").as_deref(), Some("20"));
+
+    let query: serde_json::Value = synthesis[0].body_json().unwrap();
+    assert_eq!(query["kana"], "こんにちは");
+    assert_eq!(query["speedScale"], 1.5);
+}
+
+#[tokio::test]
+async fn make_speech_scales_preset_speed() {
+    let mock = MockVoicevox::start().await;
+
+    make_speech(&mock.client(), speech_request(2, 1.2))
+        .await
+        .unwrap();
+
+    let synthesis = mock.requests(Endpoint::Synthesis).await;
+    let query: serde_json::Value = synthesis[0].body_json().unwrap();
+    let speed_scale = query["speedScale"].as_f64().unwrap();
+    assert!((speed_scale - 1.5 * 1.2).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn make_speech_fails_for_unknown_preset() {
+    let mock = MockVoicevox::start().await;
+
+    let err = make_speech(&mock.client(), speech_request(99, 1.0))
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "Preset 99 is not available");
+    assert!(mock.requests(Endpoint::Synthesis).await.is_empty());
+}
+
+#[tokio::test]
+async fn make_speech_fails_when_synthesis_fails() {
+    let mock = MockVoicevox::start().await;
+    mock.fail(Endpoint::Synthesis, 500, 1).await;
+
+    let result = make_speech(&mock.client(), speech_request(1, 1.0)).await;
+    assert!(result.is_err());
+
+    // 注入したエラーは1回のみ
+    let audio = make_speech(&mock.client(), speech_request(1, 1.0))
+        .await
+        .unwrap();
+    assert_eq!(audio, tiny_wav());
+}
+
+#[tokio::test]
+async fn make_speech_waits_for_slow_engine() {
+    const DELAY: Duration = Duration::from_millis(200);
+
+    let mock = MockVoicevox::start().await;
+    mock.delay(Endpoint::Synthesis, DELAY).await;
+
+    let started_at = Instant::now();
+    let audio = make_speech(&mock.client(), speech_request(1, 1.0))
+        .await
+        .unwrap();
+
+    assert!(started_at.elapsed() >= DELAY);
+    assert_eq!(audio, tiny_wav());
+}
+
+#[tokio::test]
+async fn initialize_speakers_initializes_every_preset_style() {
+    let mock = MockVoicevox::start().await;
+
+    let style_ids = initialize_speakers(&mock.client()).await.unwrap();
+
+    assert_eq!(style_ids, vec![10, 20]);
+    let requests = mock.requests(Endpoint::InitializeSpeaker).await;
+    let mut speakers = requests
+        .iter()
+        .map(|request| query_param(request, "speaker").unwrap())
+        .collect::<Vec<_>>();
+    speakers.sort();
+    assert_eq!(speakers, vec!["10", "20"]);
+    assert!(
+        requests
+            .iter()
+            .all(|request| query_param(request, "skip_reinit").as_deref() == Some("true"))
+    );
+}
+
+#[tokio::test]
+async fn initialize_speakers_fails_when_engine_fails() {
+    let mock = MockVoicevox::start().await;
+    mock.fail(Endpoint::InitializeSpeaker, 503, 1).await;
+
+    let result = initialize_speakers(&mock.client()).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn get_requests_are_retried_after_server_error() {
+    let mock = MockVoicevox::start().await;
+    mock.fail(Endpoint::Presets, 503, 2).await;
+
+    let presets = mock.client().presets().await.unwrap();
+
+    assert_eq!(presets.len(), PRESETS.len());
+    assert_eq!(mock.requests(Endpoint::Presets).await.len(), 3);
+}
+
+#[tokio::test]
+async fn retries_are_bounded() {
+    let mock = MockVoicevox::start().await;
+    mock.fail(Endpoint::Version, 503, 3).await;
+
+    let err = mock.client().version().await.unwrap_err();
+
+    assert!(matches!(err, VoicevoxError::Status { status, .. } if status == 503));
+    assert_eq!(mock.requests(Endpoint::Version).await.len(), 3);
+}
+
+#[tokio::test]
+async fn synthesis_is_not_retried_after_server_error() {
+    let mock = MockVoicevox::start().await;
+    mock.fail(Endpoint::Synthesis, 503, 1).await;
+
+    let err = make_speech(&mock.client(), speech_request(1, 1.0))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<VoicevoxError>(),
+        Some(VoicevoxError::Status { status, .. }) if *status == 503
+    ));
+    assert_eq!(mock.requests(Endpoint::Synthesis).await.len(), 1);
+}
+
+#[tokio::test]
+async fn hung_engine_times_out() {
+    let mock = MockVoicevox::start().await;
+    mock.delay(Endpoint::Synthesis, Duration::from_secs(10))
+        .await;
+    let client = MockVoicevox::client_with(&VoicevoxConfig {
+        request_timeout_ms: 200,
+        ..mock.config()
+    });
+
+    let started_at = Instant::now();
+    let err = make_speech(&client, speech_request(1, 1.0))
+        .await
+        .unwrap_err();
+
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+    assert!(matches!(
+        err.downcast_ref::<VoicevoxError>(),
+        Some(VoicevoxError::Unreachable(err)) if err.is_timeout()
+    ));
+}
+
+#[tokio::test]
+async fn unreachable_engine_is_reported() {
+    let mock = MockVoicevox::start().await;
+    let client = MockVoicevox::client_with(&VoicevoxConfig {
+        // 接続を受け付けないポート
+        api_base: "http://127.0.0.1:1".to_string(),
+        max_retries: 1,
+        ..mock.config()
+    });
+
+    let err = client.presets().await.unwrap_err();
+
+    assert!(matches!(err, VoicevoxError::Unreachable(_)));
+}
+
+#[tokio::test]
+async fn validation_error_includes_detail() {
+    let mock = MockVoicevox::start().await;
+    let detail = json!([{ "loc": ["query", "text"], "msg": "field required" }]);
+    mock.respond(
+        Endpoint::AudioQueryFromPreset,
+        ResponseTemplate::new(422).set_body_json(json!({ "detail": detail })),
+        1,
+    )
+    .await;
+
+    let err = make_speech(&mock.client(), speech_request(1, 1.0))
+        .await
+        .unwrap_err();
+
+    match err.downcast_ref::<VoicevoxError>() {
+        Some(VoicevoxError::Validation { detail: actual }) => assert_eq!(actual, &detail),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn unknown_preset_is_reported_by_engine() {
+    let mock = MockVoicevox::start().await;
+
+    let err = mock
+        .client()
+        .generate_query_from_preset(GenerateQueryFromPresetParams {
+            preset_id: 99,
+            text: "こんにちは".to_string(),
+        })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, VoicevoxError::InvalidPreset(99)));
+}
+
+#[tokio::test]
+async fn message_detail_for_existing_preset_is_kept_as_validation_error() {
+    let mock = MockVoicevox::start().await;
+    mock.respond(
+        Endpoint::AudioQueryFromPreset,
+        ResponseTemplate::new(422).set_body_string("Unprocessable Entity"),
+        1,
+    )
+    .await;
+
+    let err = mock
+        .client()
+        .generate_query_from_preset(GenerateQueryFromPresetParams {
+            preset_id: 1,
+            text: "こんにちは".to_string(),
+        })
+        .await
+        .unwrap_err();
+
+    match err {
+        VoicevoxError::Validation { detail } => assert_eq!(detail, "Unprocessable Entity"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn presets_are_cached_between_speeches() {
+    let mock = MockVoicevox::start().await;
+    let client = mock.client();
+
+    make_speech(&client, speech_request(1, 1.0)).await.unwrap();
+    make_speech(&client, speech_request(2, 1.0)).await.unwrap();
+    list_preset_ids(&client).await.unwrap();
+
+    assert_eq!(mock.requests(Endpoint::Presets).await.len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_preset_refreshes_cached_presets() {
+    let mock = MockVoicevox::start().await;
+    let client = mock.client();
+    list_preset_ids(&client).await.unwrap();
+
+    let err = make_speech(&client, speech_request(99, 1.0))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<VoicevoxError>(),
+        Some(VoicevoxError::InvalidPreset(99))
+    ));
+    assert_eq!(mock.requests(Endpoint::Presets).await.len(), 2);
+}
+
+#[tokio::test]
+async fn invalidated_presets_are_fetched_again() {
+    let mock = MockVoicevox::start().await;
+    let client = mock.client();
+    list_preset_ids(&client).await.unwrap();
+
+    client.invalidate_presets();
+    list_preset_ids(&client).await.unwrap();
+
+    assert_eq!(mock.requests(Endpoint::Presets).await.len(), 2);
+}
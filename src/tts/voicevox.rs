@@ -1,96 +1,316 @@
-use anyhow::Result;
-use reqwest::Url;
-use serde::Deserialize;
+use std::{
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use serde::{Deserialize, de::DeserializeOwned};
+use tracing::debug;
+
+use crate::config::VoicevoxConfig;
+
+/// The delay before the first retry, doubled on each retry
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(200);
 
 pub struct VoicevoxClient {
     client: reqwest::Client,
     api_base: String,
+    max_retries: u32,
+    /// The preset list last fetched from the engine
+    presets: RwLock<Option<Arc<[Preset]>>>,
 }
 
 impl VoicevoxClient {
-    #[must_use]
-    pub fn new(api_base: String) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            api_base,
-        }
+    pub fn new(config: &VoicevoxConfig) -> Result<Self> {
+        Url::parse(&config.api_base)
+            .with_context(|| format!("Invalid VOICEVOX ENGINE URL {}", config.api_base))?;
+
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            client,
+            api_base: config.api_base.clone(),
+            max_retries: config.max_retries,
+            presets: RwLock::new(None),
+        })
+    }
+
+    /// Returns the base URL of the VOICEVOX ENGINE this client connects to.
+    pub fn api_base(&self) -> &str {
+        &self.api_base
     }
 
     pub async fn generate_query_from_preset(
         &self,
         params: GenerateQueryFromPresetParams,
-    ) -> Result<String> {
-        let url = Url::parse_with_params(
-            &self.get_endpoint("/audio_query_from_preset"),
+    ) -> Result<String, VoicevoxError> {
+        let url = self.endpoint(
+            "/audio_query_from_preset",
             &[
                 ("text", params.text),
                 ("preset_id", params.preset_id.to_string()),
             ],
-        )?;
+        );
 
-        let resp = self
-            .client
-            .post(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .text()
-            .await?;
+        let resp = match self
+            .send(Method::POST, || self.client.post(url.clone()))
+            .await
+        {
+            // FastAPI reports invalid parameters as a list, while the engine reports an unknown
+            // preset as a message. Other errors may be reported as a message too, so make sure
+            // the preset is actually missing.
+            Err(
+                err @ VoicevoxError::Validation {
+                    detail: serde_json::Value::String(_),
+                },
+            ) => {
+                return match self.presets().await {
+                    Ok(presets) if presets.iter().all(|p| p.id != params.preset_id) => {
+                        Err(VoicevoxError::InvalidPreset(params.preset_id))
+                    }
+                    _ => Err(err),
+                };
+            }
+            resp => resp?,
+        };
 
-        Ok(resp)
+        resp.text().await.map_err(VoicevoxError::from_body)
     }
 
-    pub async fn synthesis(&self, params: SynthesisParams) -> Result<Vec<u8>> {
-        let url = Url::parse_with_params(
-            &self.get_endpoint("/synthesis"),
-            &[("speaker", params.style_id.to_string())],
-        )?;
+    pub async fn synthesis(&self, params: SynthesisParams) -> Result<Vec<u8>, VoicevoxError> {
+        let url = self.endpoint("/synthesis", &[("speaker", params.style_id.to_string())]);
 
         let resp = self
-            .client
-            .post(url)
-            .header("content-type", "application/json")
-            .body(params.query)
-            .send()
+            .send(Method::POST, || {
+                self.client
+                    .post(url.clone())
+                    .header("content-type", "application/json")
+                    .body(params.query.clone())
+            })
             .await?
-            .error_for_status()?
             .bytes()
-            .await?;
+            .await
+            .map_err(VoicevoxError::from_body)?;
 
         Ok(resp.to_vec())
     }
 
-    pub async fn presets(&self) -> Result<Vec<Preset>> {
-        let url = Url::parse(&self.get_endpoint("/presets"))?;
+    /// Fetches the preset list from the engine and caches it.
+    pub async fn presets(&self) -> Result<Vec<Preset>, VoicevoxError> {
+        let presets: Vec<Preset> = self.get_json("/presets", &[]).await?;
+        *self.presets.write().unwrap_or_else(PoisonError::into_inner) =
+            Some(presets.as_slice().into());
+        Ok(presets)
+    }
 
-        let resp = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
+    /// Returns the cached preset list, fetching it if it has not been fetched yet.
+    ///
+    /// The cache is refreshed whenever [`Self::presets`] is called.
+    pub async fn cached_presets(&self) -> Result<Arc<[Preset]>, VoicevoxError> {
+        let cached = self
+            .presets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        match cached {
+            Some(presets) => Ok(presets),
+            None => Ok(self.presets().await?.into()),
+        }
+    }
+
+    /// Discards the cached preset list so that the next [`Self::cached_presets`] fetches it.
+    pub fn invalidate_presets(&self) {
+        *self.presets.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
 
-        Ok(resp)
+    pub async fn version(&self) -> Result<String, VoicevoxError> {
+        self.get_json("/version", &[]).await
     }
 
-    pub async fn initialize_speaker(&self, speaker_id: i64, skip_reinit: bool) -> Result<()> {
-        let url = Url::parse_with_params(
-            &self.get_endpoint("/initialize_speaker"),
+    pub async fn initialize_speaker(
+        &self,
+        speaker_id: i64,
+        skip_reinit: bool,
+    ) -> Result<(), VoicevoxError> {
+        let url = self.endpoint(
+            "/initialize_speaker",
             &[
                 ("speaker", speaker_id.to_string()),
                 ("skip_reinit", skip_reinit.to_string()),
             ],
-        )?;
+        );
 
-        self.client.post(url).send().await?.error_for_status()?;
+        self.send(Method::POST, || self.client.post(url.clone()))
+            .await?;
 
         Ok(())
     }
 
-    fn get_endpoint(&self, path: impl AsRef<str>) -> String {
-        self.api_base.clone() + path.as_ref()
+    pub async fn is_initialized_speaker(&self, speaker_id: i64) -> Result<bool, VoicevoxError> {
+        self.get_json(
+            "/is_initialized_speaker",
+            &[("speaker", speaker_id.to_string())],
+        )
+        .await
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, VoicevoxError> {
+        let url = self.endpoint(path, params);
+
+        self.send(Method::GET, || self.client.get(url.clone()))
+            .await?
+            .json()
+            .await
+            .map_err(VoicevoxError::from_body)
+    }
+
+    /// Sends the request built by `build`, retrying up to `max_retries` times on transient errors.
+    ///
+    /// A request that failed to connect never reached the engine, so it is retried regardless of
+    /// `method`. Timeouts and server errors are retried only for GET requests, since retrying a
+    /// synthesis the engine is struggling with would only make things worse.
+    async fn send(
+        &self,
+        method: Method,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, VoicevoxError> {
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut retries = 0;
+
+        loop {
+            let result = match build().send().await {
+                Ok(resp) => check_status(resp).await,
+                Err(err) => Err(VoicevoxError::Unreachable(err)),
+            };
+
+            match result {
+                Err(err) if retries < self.max_retries && err.is_retryable(&method) => {
+                    retries += 1;
+                    debug!(
+                        "{err}; retrying in {delay:?} ({retries}/{})",
+                        self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn endpoint(&self, path: &str, params: &[(&str, String)]) -> Url {
+        let mut url = Url::parse(&format!("{}{path}", self.api_base))
+            .expect("api_base has been validated in VoicevoxClient::new");
+        if !params.is_empty() {
+            url.query_pairs_mut().extend_pairs(params);
+        }
+        url
+    }
+}
+
+/// Turns a non-successful response into an error.
+async fn check_status(resp: Response) -> Result<Response, VoicevoxError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+
+    let body = resp.text().await.unwrap_or_default();
+    if status == StatusCode::UNPROCESSABLE_ENTITY {
+        let detail = match serde_json::from_str::<ErrorBody>(&body) {
+            Ok(ErrorBody { detail }) => detail,
+            Err(_) => serde_json::Value::String(body),
+        };
+        return Err(VoicevoxError::Validation { detail });
+    }
+
+    Err(VoicevoxError::Status { status, body })
+}
+
+/// The body of an error response of the engine
+#[derive(Deserialize)]
+struct ErrorBody {
+    detail: serde_json::Value,
+}
+
+/// An error from the VOICEVOX ENGINE API.
+///
+/// The methods of [`VoicevoxClient`] return this type, so callers that get an [`anyhow::Error`]
+/// can tell the cause with [`anyhow::Error::downcast_ref`].
+#[derive(Debug)]
+pub enum VoicevoxError {
+    /// The engine could not be connected to or did not respond in time
+    Unreachable(reqwest::Error),
+    /// The preset does not exist in the engine
+    InvalidPreset(i64),
+    /// The engine rejected the parameters (HTTP 422)
+    Validation {
+        /// The `detail` field of the response, or the whole body if it is not JSON
+        detail: serde_json::Value,
+    },
+    /// The engine responded with any other error status
+    Status { status: StatusCode, body: String },
+    /// The response body could not be read or decoded
+    InvalidResponse(reqwest::Error),
+}
+
+impl VoicevoxError {
+    fn from_body(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Self::Unreachable(err)
+        } else {
+            Self::InvalidResponse(err)
+        }
+    }
+
+    fn is_retryable(&self, method: &Method) -> bool {
+        match self {
+            Self::Unreachable(err) if err.is_connect() => true,
+            Self::Unreachable(_) => method == Method::GET,
+            Self::Status { status, .. } => status.is_server_error() && method == Method::GET,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for VoicevoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreachable(err) if err.is_timeout() => {
+                f.write_str("VOICEVOX ENGINE did not respond in time")
+            }
+            Self::Unreachable(_) => f.write_str("VOICEVOX ENGINE is unreachable"),
+            Self::InvalidPreset(id) => write!(f, "Preset {id} is not available"),
+            Self::Validation { detail } => match detail {
+                serde_json::Value::String(detail) => {
+                    write!(f, "VOICEVOX ENGINE rejected the request: {detail}")
+                }
+                detail => write!(f, "VOICEVOX ENGINE rejected the request: {detail}"),
+            },
+            Self::Status { status, body } => {
+                write!(f, "VOICEVOX ENGINE responded with {status}: {body}")
+            }
+            Self::InvalidResponse(_) => f.write_str("Invalid response from VOICEVOX ENGINE"),
+        }
+    }
+}
+
+impl std::error::Error for VoicevoxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unreachable(err) | Self::InvalidResponse(err) => Some(err),
+            _ => None,
+        }
     }
 }
 
@@ -0,0 +1,4 @@
+/// Helper function to sanitize text for Discord responses
+pub fn sanitize_response(text: &str) -> String {
+    format!("`{}`", text.replace('`', ""))
+}
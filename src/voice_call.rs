@@ -1,12 +1,14 @@
-use std::sync::Arc;
+use std::{fmt, sync::Arc, time::Duration};
 
 use anyhow::{Context as _, Result};
-use serenity::client::Context;
+use serenity::{client::Context, model::id::UserId};
 use songbird::{
     Call, Songbird,
     id::{ChannelId, GuildId},
+    tracks::{Track, TrackHandle},
 };
 use tokio::sync::Mutex;
+use tracing::instrument;
 
 pub async fn join_deaf(
     ctx: &Context,
@@ -50,16 +52,253 @@ pub async fn is_connected(ctx: &Context, guild_id: impl Into<GuildId>) -> Result
     Ok(is_connected)
 }
 
-pub async fn enqueue(ctx: &Context, guild_id: impl Into<GuildId>, audio: Vec<u8>) -> Result<()> {
+/// Information about a message attached to its track in the queue
+#[derive(Debug, Clone)]
+pub struct TrackMetadata {
+    pub author_id: UserId,
+    pub author_name: String,
+    pub text: String,
+    /// The number of dropped messages if this track is a summary of them
+    pub summarized_count: Option<usize>,
+}
+
+/// An error meaning that a message was dropped because the queue was full
+#[derive(Debug)]
+pub struct QueueFull;
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Queue is full")
+    }
+}
+
+impl std::error::Error for QueueFull {}
+
+/// Adds the audio to the end of the queue.
+///
+/// Fails with [`QueueFull`] if the queue already has `max_length` tracks, including the current
+/// track.
+#[instrument(level = "debug", skip_all)]
+pub async fn enqueue(
+    ctx: &Context,
+    guild_id: impl Into<GuildId>,
+    audio: Vec<u8>,
+    metadata: TrackMetadata,
+    max_length: usize,
+) -> Result<()> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let mut handler = call.lock().await;
+    if handler.queue().len() >= max_length {
+        return Err(QueueFull.into());
+    }
+
+    handler
+        .enqueue(Track::new_with_data(audio.into(), Arc::new(metadata)))
+        .await;
+
+    Ok(())
+}
+
+/// Adds the audio right after the current track so that it is played next.
+/// Unlike [`enqueue`], this is not limited by the length of the queue.
+pub async fn enqueue_next(
+    ctx: &Context,
+    guild_id: impl Into<GuildId>,
+    audio: Vec<u8>,
+    metadata: TrackMetadata,
+) -> Result<()> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let mut handler = call.lock().await;
+    handler
+        .enqueue(Track::new_with_data(audio.into(), Arc::new(metadata)))
+        .await;
+
+    handler.queue().modify_queue(|queue| {
+        if queue.len() > 2
+            && let Some(track) = queue.pop_back()
+        {
+            queue.insert(1, track);
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns the number of tracks waiting to be played, excluding the current track.
+pub async fn count_pending(ctx: &Context, guild_id: impl Into<GuildId>) -> Result<usize> {
     let manager = extract_songbird(ctx).await?;
     let call = get_call(&manager, guild_id)?;
 
+    let handler = call.lock().await;
+    let count = handler.queue().len().saturating_sub(1);
+
+    Ok(count)
+}
+
+/// Returns the number of tracks in the queue, including the current track.
+/// Returns `None` if Koe is not connected to a voice channel in the guild.
+pub async fn queue_length(manager: &Songbird, guild_id: impl Into<GuildId>) -> Option<usize> {
+    let call = manager.get(guild_id)?;
+    let handler = call.lock().await;
+    Some(handler.queue().len())
+}
+
+/// Returns the voice channel Koe is connected to in the guild.
+pub async fn current_channel(
+    manager: &Songbird,
+    guild_id: impl Into<GuildId>,
+) -> Option<ChannelId> {
+    let call = manager.get(guild_id)?;
+    let handler = call.lock().await;
+    handler.current_channel()
+}
+
+/// Stops the current track, clears the queue and plays the audio instead.
+pub async fn replace_queue(
+    manager: &Songbird,
+    guild_id: impl Into<GuildId>,
+    audio: Vec<u8>,
+    metadata: TrackMetadata,
+) -> Result<()> {
+    let call = get_call(manager, guild_id)?;
+
     let mut handler = call.lock().await;
-    handler.enqueue_input(audio.into()).await;
+    handler.queue().stop();
+    handler
+        .enqueue(Track::new_with_data(audio.into(), Arc::new(metadata)))
+        .await;
+
+    Ok(())
+}
+
+/// Waits until all tracks in the queue have been played, or Koe has left the voice channel.
+pub async fn wait_until_played(manager: &Songbird, guild_id: impl Into<GuildId>) {
+    let guild_id = guild_id.into();
+
+    while queue_length(manager, guild_id)
+        .await
+        .is_some_and(|length| length > 0)
+    {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
 
+/// Leaves the voice channel without a [`Context`], e.g. while shutting down.
+pub async fn disconnect(manager: &Songbird, guild_id: impl Into<GuildId>) -> Result<()> {
+    manager.remove(guild_id).await?;
     Ok(())
 }
 
+/// Drops the oldest tracks waiting to be played so that at most `keep` of them remain.
+/// Returns the number of dropped messages, counting the messages summarized by dropped summaries.
+pub async fn drop_oldest_pending(
+    ctx: &Context,
+    guild_id: impl Into<GuildId>,
+    keep: usize,
+) -> Result<usize> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let handler = call.lock().await;
+    let dropped_tracks = handler.queue().modify_queue(|queue| {
+        let pending = queue.len().saturating_sub(1);
+        let drop_count = pending.saturating_sub(keep);
+        if drop_count == 0 {
+            return Vec::new();
+        }
+        queue.drain(1..=drop_count).collect()
+    });
+
+    let mut count = 0;
+    for track in &dropped_tracks {
+        count += track.data::<TrackMetadata>().summarized_count.unwrap_or(1);
+        // An error just implies that the track has already been stopped.
+        drop(track.stop());
+    }
+
+    Ok(count)
+}
+
+/// Returns the metadata of the queued tracks. The first one is the track currently being played.
+pub async fn list_queue(
+    ctx: &Context,
+    guild_id: impl Into<GuildId>,
+) -> Result<Vec<Arc<TrackMetadata>>> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let handler = call.lock().await;
+    let list = handler
+        .queue()
+        .current_queue()
+        .iter()
+        .map(TrackHandle::data::<TrackMetadata>)
+        .collect();
+
+    Ok(list)
+}
+
+/// Stops the current track and clears the queue. Returns the number of removed tracks.
+pub async fn clear(ctx: &Context, guild_id: impl Into<GuildId>) -> Result<usize> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let handler = call.lock().await;
+    let count = handler.queue().len();
+    handler.queue().stop();
+
+    Ok(count)
+}
+
+/// Removes the tracks of the given author from the queue, including the current track.
+/// Returns the number of removed tracks.
+pub async fn remove_by_author(
+    ctx: &Context,
+    guild_id: impl Into<GuildId>,
+    author_id: UserId,
+) -> Result<usize> {
+    let manager = extract_songbird(ctx).await?;
+    let call = get_call(&manager, guild_id)?;
+
+    let handler = call.lock().await;
+    let is_target = |track: &TrackHandle| track.data::<TrackMetadata>().author_id == author_id;
+
+    let removed_tracks = handler.queue().modify_queue(|queue| {
+        // The current track is stopped below instead of being removed here, so that the queue
+        // moves on to the next track.
+        let mut removed = Vec::new();
+        let mut idx = 1;
+        while idx < queue.len() {
+            if is_target(&queue[idx]) {
+                removed.extend(queue.remove(idx));
+            } else {
+                idx += 1;
+            }
+        }
+        removed
+    });
+    for track in &removed_tracks {
+        // An error just implies that the track has already been stopped.
+        drop(track.stop());
+    }
+    let mut count = removed_tracks.len();
+
+    if let Some(current_track) = handler.queue().current()
+        && is_target(&current_track)
+    {
+        current_track
+            .stop()
+            .context("Failed to stop current track")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
 pub async fn skip(ctx: &Context, guild_id: impl Into<GuildId>) -> Result<()> {
     let manager = extract_songbird(ctx).await?;
     let call = get_call(&manager, guild_id)?;
@@ -1,9 +1,9 @@
 use anyhow::{Context as _, Result};
-use log::debug;
 use serenity::{
     client::Context,
     model::id::{ChannelId, GuildId, UserId},
 };
+use tracing::debug;
 
 use crate::{app_state, voice_call};
 
@@ -35,6 +35,36 @@ pub async fn handle_update(ctx: &Context, guild_id: Option<GuildId>) -> Result<(
     Ok(())
 }
 
+/// Returns whether the user is in the voice channel where Koe is connected.
+pub fn is_in_current_voice_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<bool> {
+    let Some(current_voice_channel_id) = get_current_voice_channel_id(ctx, guild_id)? else {
+        return Ok(false);
+    };
+
+    let guild = guild_id
+        .to_guild_cached(&ctx.cache)
+        .context("Failed to find guild in the cache")?;
+
+    let is_in_channel = guild
+        .voice_states
+        .get(&user_id)
+        .is_some_and(|state| state.channel_id == Some(current_voice_channel_id));
+
+    Ok(is_in_channel)
+}
+
+/// Returns whether anyone other than Koe is in the voice channel.
+pub fn has_listeners(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Result<bool> {
+    let current_user_id = ctx.cache.current_user().id;
+    let users = list_users_in_voice_channel(ctx, guild_id, channel_id)?;
+
+    Ok(users.iter().any(|user_id| *user_id != current_user_id))
+}
+
 fn get_current_voice_channel_id(ctx: &Context, guild_id: GuildId) -> Result<Option<ChannelId>> {
     let current_user_id = ctx.cache.current_user().id;
 
@@ -0,0 +1,98 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use tracing::{info, warn};
+
+use crate::{
+    app_state::AppState,
+    tts::{speech::initialize_speakers, voicevox::VoicevoxClient},
+};
+
+/// The delay before the first retry, doubled on each failure up to [`MAX_RETRY_DELAY`]
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_mins(1);
+
+/// The interval of checking whether the engine still has the speakers initialized
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Spawns a task that keeps the speakers of the VOICEVOX ENGINE initialized.
+///
+/// The task retries with backoff until the engine becomes reachable, and initializes the speakers
+/// again when the engine restarts or is replaced by reloading the config.
+/// [`AppState::warmed_up`] is true while the speakers are known to be initialized.
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        loop {
+            let client = state.voicevox_client();
+
+            let Some(style_ids) = warm_up(&state, &client).await else {
+                continue;
+            };
+            // The client may have been replaced while the last request was in flight
+            if !state.mark_warmed_up(&client) {
+                continue;
+            }
+
+            watch_engine(&state, &client, &style_ids).await;
+            state.warmed_up.send_replace(false);
+        }
+    });
+}
+
+/// Initializes the speakers, retrying until it succeeds.
+/// Returns `None` if the client has been replaced in the meantime.
+async fn warm_up(state: &AppState, client: &Arc<VoicevoxClient>) -> Option<Vec<i64>> {
+    let mut delay = INITIAL_RETRY_DELAY;
+
+    loop {
+        match initialize_speakers(client).await {
+            Ok(style_ids) => return Some(style_ids),
+            Err(err) => warn!(
+                "{:?}",
+                err.context(format!(
+                    "Failed to initialize speakers; retrying in {} seconds",
+                    delay.as_secs()
+                ))
+            ),
+        }
+
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+
+        if !Arc::ptr_eq(&state.voicevox_client(), client) {
+            return None;
+        }
+    }
+}
+
+/// Returns once the speakers need to be initialized again.
+async fn watch_engine(state: &AppState, client: &Arc<VoicevoxClient>, style_ids: &[i64]) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        if !Arc::ptr_eq(&state.voicevox_client(), client) {
+            info!("VOICEVOX ENGINE has been replaced; initializing speakers again");
+            return;
+        }
+
+        // A restarted engine has none of the speakers initialized, so checking one is enough
+        let Some(&style_id) = style_ids.first() else {
+            continue;
+        };
+        match client
+            .is_initialized_speaker(style_id)
+            .await
+            .context("Failed to reach VOICEVOX ENGINE")
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("VOICEVOX ENGINE seems to have restarted; initializing speakers again");
+                return;
+            }
+            Err(err) => {
+                warn!("{err:?}");
+                return;
+            }
+        }
+    }
+}